@@ -0,0 +1,202 @@
+use std::io::{self, Write};
+
+use crate::explain::json_string;
+use crate::scanner::{self, Token};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    /// `.json` files get JSON, anything else CSV.
+    pub fn from_path(path: &str) -> Format {
+        match path.ends_with(".json") {
+            true => Format::Json,
+            false => Format::Csv,
+        }
+    }
+}
+
+/// Why a line contributed 0 to a part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// No literal digit, so nothing for part 1.
+    NoDigit,
+    /// Neither a digit nor a spelled digit, so nothing for either part.
+    NoToken,
+}
+
+impl Flag {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Flag::NoDigit => "no_digit",
+            Flag::NoToken => "no_token",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Record {
+    line: usize,
+    first: Option<(String, Token)>,
+    last: Option<(String, Token)>,
+    part1: u32,
+    part2: u32,
+    flag: Option<Flag>,
+}
+
+#[derive(Debug)]
+pub struct Audit {
+    records: Vec<Record>,
+}
+
+impl Audit {
+    pub fn new() -> Audit {
+        Audit { records: vec![] }
+    }
+
+    /// Adds the line with its tokens, as returned by `Scanner::tokens`, and the values it gave.
+    pub fn add(&mut self, line: usize, text: &str, tokens: &[Token], part1: u32, part2: u32) {
+        let flag = match (tokens.is_empty(), tokens.iter().any(|token| !token.spelled)) {
+            (true, _) => Some(Flag::NoToken),
+            (false, false) => Some(Flag::NoDigit),
+            (false, true) => None,
+        };
+        let (first, last) = match scanner::first_and_last(tokens) {
+            Some((first, last)) => (
+                Some((first.text(text).to_string(), first)),
+                Some((last.text(text).to_string(), last)),
+            ),
+            None => (None, None),
+        };
+        self.records.push(Record {
+            line,
+            first,
+            last,
+            part1,
+            part2,
+            flag,
+        });
+    }
+
+    pub fn write(&self, format: Format, out: &mut impl Write) -> io::Result<()> {
+        match format {
+            Format::Csv => self.write_csv(out),
+            Format::Json => self.write_json(out),
+        }
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "line,first,first_kind,first_start,first_end,last,last_kind,last_start,last_end,part1,part2,flag"
+        )?;
+        for record in self.records.iter() {
+            write!(out, "{}", record.line)?;
+            for token in [&record.first, &record.last] {
+                match token {
+                    Some((text, token)) => write!(
+                        out,
+                        ",{},{},{},{}",
+                        csv_field(text),
+                        kind(token),
+                        token.start,
+                        token.end
+                    )?,
+                    None => write!(out, ",,,,")?,
+                }
+            }
+            writeln!(
+                out,
+                ",{},{},{}",
+                record.part1,
+                record.part2,
+                record.flag.map_or("", |flag| flag.as_str())
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_json(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "[")?;
+        for (i, record) in self.records.iter().enumerate() {
+            write!(out, "  {{\"line\":{}", record.line)?;
+            for (name, token) in [("first", &record.first), ("last", &record.last)] {
+                match token {
+                    Some((text, token)) => write!(
+                        out,
+                        ",\"{}\":{{\"text\":{},\"kind\":\"{}\",\"start\":{},\"end\":{}}}",
+                        name,
+                        json_string(text),
+                        kind(token),
+                        token.start,
+                        token.end
+                    )?,
+                    None => write!(out, ",\"{}\":null", name)?,
+                }
+            }
+            write!(
+                out,
+                ",\"part1\":{},\"part2\":{},\"flag\":{}}}",
+                record.part1,
+                record.part2,
+                record
+                    .flag
+                    .map_or("null".to_string(), |flag| json_string(flag.as_str()))
+            )?;
+            match i + 1 == self.records.len() {
+                true => writeln!(out)?,
+                false => writeln!(out, ",")?,
+            }
+        }
+        writeln!(out, "]")
+    }
+}
+
+fn kind(token: &Token) -> &'static str {
+    match token.spelled {
+        true => "word",
+        false => "digit",
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    match text.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn test_csv_flags() {
+        let scanner = Scanner::english();
+        let mut audit = Audit::new();
+        for (line, text, part1, part2) in [
+            (1, "two1nine", 11, 29),
+            (2, "eightwo", 0, 82),
+            (3, "abc", 0, 0),
+        ] {
+            audit.add(line, text, &scanner.tokens(text).unwrap(), part1, part2);
+        }
+
+        let mut out: Vec<u8> = vec![];
+        audit.write(Format::Csv, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = csv.lines().skip(1).collect();
+        assert_eq!(
+            rows,
+            vec![
+                "1,two,word,0,3,nine,word,4,8,11,29,",
+                "2,eight,word,0,5,two,word,4,7,0,82,no_digit",
+                "3,,,,,,,,,0,0,no_token",
+            ]
+        );
+    }
+}
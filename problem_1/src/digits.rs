@@ -0,0 +1,106 @@
+use std::fmt;
+
+/// First code point of every run of ten Unicode decimal digits (general category Nd), as of
+/// Unicode 14.
+const DECIMAL_ZEROS: [u32; 66] = [
+    0x0030, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0, 0x16A60, 0x16AC0,
+    0x16B50, 0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E950, 0x1FBF0,
+];
+
+/// Value of `c` if it is a decimal digit in any script.
+pub fn decimal_value(c: char) -> Option<u32> {
+    let code = c as u32;
+    let index = DECIMAL_ZEROS.partition_point(|zero| *zero <= code);
+    let zero = *DECIMAL_ZEROS.get(index.checked_sub(1)?)?;
+    match code - zero {
+        value @ 0..=9 => Some(value),
+        _ => None,
+    }
+}
+
+/// What to do with decimal digits outside of ASCII, like '٣' or '৭'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigitPolicy {
+    Ignore,
+    Unicode,
+    Error,
+}
+
+impl DigitPolicy {
+    pub fn from_arg(arg: &str) -> Option<DigitPolicy> {
+        match arg {
+            "ignore" => Some(DigitPolicy::Ignore),
+            "unicode" => Some(DigitPolicy::Unicode),
+            "error" => Some(DigitPolicy::Error),
+            _ => None,
+        }
+    }
+
+    /// Value of `c` as a calibration digit, `start` being its byte offset in the line.
+    pub fn digit(&self, c: char, start: usize) -> Result<Option<u32>, NonAsciiDigit> {
+        if c.is_ascii_digit() {
+            return Ok(c.to_digit(10));
+        }
+        match (self, decimal_value(c)) {
+            (_, None) | (DigitPolicy::Ignore, _) => Ok(None),
+            (DigitPolicy::Unicode, value) => Ok(value),
+            (DigitPolicy::Error, Some(_)) => Err(NonAsciiDigit { c, start }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonAsciiDigit {
+    pub c: char,
+    pub start: usize,
+}
+
+impl fmt::Display for NonAsciiDigit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "non-ASCII digit '{}' (U+{:04X}) at byte {}",
+            self.c, self.c as u32, self.start
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decimal_value() {
+        assert_eq!(decimal_value('7'), Some(7));
+        assert_eq!(decimal_value('٣'), Some(3));
+        assert_eq!(decimal_value('৭'), Some(7));
+        assert_eq!(decimal_value('９'), Some(9));
+        assert_eq!(decimal_value('𝟘'), Some(0));
+        assert_eq!(decimal_value('½'), None);
+        assert_eq!(decimal_value('Ⅻ'), None);
+        assert_eq!(decimal_value('a'), None);
+        assert_eq!(decimal_value('/'), None);
+    }
+
+    #[test]
+    fn test_policies() {
+        assert_eq!(DigitPolicy::Ignore.digit('٣', 0), Ok(None));
+        assert_eq!(DigitPolicy::Unicode.digit('٣', 0), Ok(Some(3)));
+        assert_eq!(
+            DigitPolicy::Error.digit('٣', 4),
+            Err(NonAsciiDigit { c: '٣', start: 4 })
+        );
+        for policy in [
+            DigitPolicy::Ignore,
+            DigitPolicy::Unicode,
+            DigitPolicy::Error,
+        ] {
+            assert_eq!(policy.digit('5', 0), Ok(Some(5)));
+            assert_eq!(policy.digit('½', 0), Ok(None));
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Jsonl,
+}
+
+impl Format {
+    fn from_arg(arg: &str) -> Option<Format> {
+        match arg {
+            "--explain" | "--explain=text" => Some(Format::Text),
+            "--explain=jsonl" => Some(Format::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// Removes the `--explain[=text|jsonl]` flag from the arguments, if present.
+pub fn take_flag(args: &mut Vec<String>) -> Option<Format> {
+    let position = args
+        .iter()
+        .position(|arg| Format::from_arg(arg).is_some())?;
+    Format::from_arg(&args.remove(position))
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Bool(bool),
+    Num(i64),
+    Str(String),
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Value {
+        Value::Num(value as i64)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Value {
+        Value::Num(value as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Value {
+        Value::Num(value)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Value {
+        Value::Num(value as i64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Value {
+        Value::Str(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::Str(value)
+    }
+}
+
+#[derive(Debug)]
+struct Event {
+    record: usize,
+    kind: &'static str,
+    fields: Vec<(&'static str, Value)>,
+}
+
+/// Collects one event per input record when `--explain` is given, does nothing otherwise.
+#[derive(Debug)]
+pub struct Trace {
+    format: Option<Format>,
+    events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn new(format: Option<Format>) -> Trace {
+        Trace {
+            format,
+            events: vec![],
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.format.is_some()
+    }
+
+    pub fn record(
+        &mut self,
+        record: usize,
+        kind: &'static str,
+        fields: Vec<(&'static str, Value)>,
+    ) {
+        if self.enabled() {
+            self.events.push(Event {
+                record,
+                kind,
+                fields,
+            });
+        }
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        match self.format {
+            None => Ok(()),
+            Some(Format::Text) => self.write_text(out),
+            Some(Format::Jsonl) => self.write_jsonl(out),
+        }
+    }
+
+    fn write_text(&self, out: &mut impl Write) -> io::Result<()> {
+        for event in self.events.iter() {
+            write!(out, "[{:>5}] {:<10}", event.record, event.kind)?;
+            for (name, value) in event.fields.iter() {
+                match value {
+                    Value::Bool(b) => write!(out, " {}={}", name, b)?,
+                    Value::Num(n) => write!(out, " {}={}", name, n)?,
+                    Value::Str(s) => write!(out, " {}={:?}", name, s)?,
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    fn write_jsonl(&self, out: &mut impl Write) -> io::Result<()> {
+        for event in self.events.iter() {
+            write!(
+                out,
+                "{{\"record\":{},\"kind\":{}",
+                event.record,
+                json_string(event.kind)
+            )?;
+            for (name, value) in event.fields.iter() {
+                match value {
+                    Value::Bool(b) => write!(out, ",{}:{}", json_string(name), b)?,
+                    Value::Num(n) => write!(out, ",{}:{}", json_string(name), n)?,
+                    Value::Str(s) => write!(out, ",{}:{}", json_string(name), json_string(s))?,
+                }
+            }
+            writeln!(out, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn written(format: Option<Format>) -> String {
+        let mut trace = Trace::new(format);
+        trace.record(
+            1,
+            "line",
+            vec![("value", 12_u32.into()), ("kept", true.into())],
+        );
+        trace.record(
+            12,
+            "card",
+            vec![("text", "say \"hi\"\n".into()), ("delta", (-3_i64).into())],
+        );
+        let mut out: Vec<u8> = vec![];
+        trace.write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(json_string("1\n2\r3\t4"), "\"1\\n2\\r3\\t4\"");
+        assert_eq!(json_string("\u{1}é"), "\"\\u0001é\"");
+    }
+
+    #[test]
+    fn test_write() {
+        assert_eq!(
+            written(Some(Format::Text)),
+            concat!(
+                "[    1] line       value=12 kept=true\n",
+                "[   12] card       text=\"say \\\"hi\\\"\\n\" delta=-3\n",
+            )
+        );
+        assert_eq!(
+            written(Some(Format::Jsonl)),
+            concat!(
+                "{\"record\":1,\"kind\":\"line\",\"value\":12,\"kept\":true}\n",
+                "{\"record\":12,\"kind\":\"card\",\"text\":\"say \\\"hi\\\"\\n\",\"delta\":-3}\n",
+            )
+        );
+        assert_eq!(written(None), "");
+    }
+
+    #[test]
+    fn test_take_flag() {
+        let mut args: Vec<String> = ["prog", "--explain=jsonl", "input.txt"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(take_flag(&mut args), Some(Format::Jsonl));
+        assert_eq!(args, vec!["prog", "input.txt"]);
+        assert_eq!(take_flag(&mut args), None);
+    }
+}
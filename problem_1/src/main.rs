@@ -1,152 +1,181 @@
-/// Brute force solution, didn't put much though into it
+//! Brute force solution, didn't put much though into it
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+mod audit;
+mod digits;
+mod explain;
+mod parallel;
+mod scanner;
 
-fn check3lettered(slice: &str) -> Option<u32> {
-    match slice {
-        "one" => Some(1),
-        "two" => Some(2),
-        "six" => Some(6),
-        _ => None,
-    }
-}
-
-fn check4lettered(slice: &str) -> Option<u32> {
-    match slice {
-        "four" => Some(4),
-        "five" => Some(5),
-        "nine" => Some(9),
-        _ => None,
-    }
-}
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter};
+use std::process;
 
-fn check5lettered(slice: &str) -> Option<u32> {
-    match slice {
-        "three" => Some(3),
-        "seven" => Some(7),
-        "eight" => Some(8),
-        _ => None,
-    }
-}
+use audit::Audit;
+use digits::{DigitPolicy, NonAsciiDigit};
+use explain::Trace;
+use scanner::Scanner;
 
-fn part1(line: &String) -> u32 {
+fn part1(line: &str, policy: DigitPolicy) -> Result<u32, NonAsciiDigit> {
     let mut code: u32 = 0;
-    for c in line.chars() {
-        if c.is_numeric() {
-            code = c.to_digit(10).unwrap() * 10;
+    for (i, c) in line.char_indices() {
+        if let Some(digit) = policy.digit(c, i)? {
+            code = digit * 10;
             break;
         }
     }
-    for c in line.chars().rev() {
-        if c.is_numeric() {
-            code += c.to_digit(10).unwrap();
+    for (i, c) in line.char_indices().rev() {
+        if let Some(digit) = policy.digit(c, i)? {
+            code += digit;
             break;
         }
     }
 
-    code
+    Ok(code)
 }
 
-fn part2(line: &String) -> u32 {
-    let mut code: u32 = 0;
-    let len = line.len();
-    for (i, c) in line.char_indices() {
-        if c.is_numeric() {
-            code = c.to_digit(10).unwrap() * 10;
-            break;
-        } else if "otfsen".contains(c) {
-            let remaining = len - (i + 1);
-            match remaining {
-                0 | 1 | 2 => {}
-                3 => {
-                    if let Some(n) = check3lettered(&line[i..(i + 3)]) {
-                        code = n * 10;
-                        break;
-                    }
-                }
-                4 => {
-                    if let Some(n) = check3lettered(&line[i..(i + 3)]) {
-                        code = n * 10;
-                        break;
-                    }
-                    if let Some(n) = check4lettered(&line[i..(i + 4)]) {
-                        code = n * 10;
-                        break;
-                    }
-                }
-                5 | _ => {
-                    if let Some(n) = check3lettered(&line[i..(i + 3)]) {
-                        code = n * 10;
-                        break;
-                    }
-                    if let Some(n) = check4lettered(&line[i..(i + 4)]) {
-                        code = n * 10;
-                        break;
-                    }
-                    if let Some(n) = check5lettered(&line[i..(i + 5)]) {
-                        code = n * 10;
-                        break;
-                    }
-                }
-            };
+/// Part 2 value of a line, spelled digits included.
+fn calibrate(line: &str, scanner: &Scanner) -> Result<u32, NonAsciiDigit> {
+    Ok(scanner.first_and_last(line)?.map_or(0, |(first, last)| {
+        first.leading_digit() * 10 + last.trailing_digit()
+    }))
+}
+
+/// Removes `name <value>` from the arguments and returns the value.
+fn take_value(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let position = args.iter().position(|arg| arg == name)?;
+    if position + 1 >= args.len() {
+        println!("Missing value for {}", name);
+        process::exit(1);
+    }
+    args.remove(position);
+    Some(args.remove(position))
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    let mut trace = Trace::new(explain::take_flag(&mut args));
+    // A custom word table, one `word value` pair per line, replaces the english digit words
+    let scanner = match take_value(&mut args, "--words") {
+        Some(path) => Scanner::from_table(&fs::read_to_string(path).unwrap()).unwrap_or_else(|e| {
+            println!("Invalid word table: {}", e);
+            process::exit(1);
+        }),
+        None => Scanner::english(),
+    };
+    let policy = match take_value(&mut args, "--digits") {
+        Some(policy) => DigitPolicy::from_arg(&policy).unwrap_or_else(|| {
+            println!(
+                "Unknown digit policy '{}', expected ignore, unicode or error",
+                policy
+            );
+            process::exit(1);
+        }),
+        None => DigitPolicy::Ignore,
+    };
+    let scanner = scanner.with_policy(policy);
+    let audit_path = take_value(&mut args, "--audit");
+    let mut audit = Audit::new();
+
+    // Splits the file in newline-aligned chunks summed on that many threads
+    if let Some(threads) = take_value(&mut args, "--threads") {
+        if trace.enabled() || audit_path.is_some() {
+            println!("--threads can't be combined with --explain or --audit");
+            process::exit(1);
+        }
+        let threads: usize = threads.parse().unwrap_or_else(|_| {
+            println!("Invalid thread count '{}'", threads);
+            process::exit(1);
+        });
+        match parallel::sum("./input.txt", &scanner, policy, threads) {
+            Ok(totals) => {
+                println!("Result part1: {}", totals.part1);
+                println!("Result part2: {}", totals.part2);
+            }
+            Err(parallel::ChunkError::Digit { line, error }) => {
+                println!("Line {}: {}", line, error);
+                process::exit(1);
+            }
+            Err(parallel::ChunkError::Io(error)) => panic!("{}", error),
         }
+        return;
     }
 
-    for (i, c) in line.char_indices().rev() {
-        if c.is_numeric() {
-            code += c.to_digit(10).unwrap();
-            break;
-        } else if "eorxnt".contains(c) {
-            let remaining = i;
-            match remaining {
-                0 | 1 | 2 => {}
-                3 => {
-                    if let Some(n) = check3lettered(&line[(i - 2)..(i + 1)]) {
-                        code += n;
-                        break;
-                    }
-                }
-                4 => {
-                    if let Some(n) = check3lettered(&line[(i - 2)..(i + 1)]) {
-                        code += n;
-                        break;
-                    }
-                    if let Some(n) = check4lettered(&line[(i - 3)..(i + 1)]) {
-                        code += n;
-                        break;
-                    }
+    let file = File::open("./input.txt").unwrap();
+    let mut total_part1: u64 = 0;
+    let mut total_part2: u64 = 0;
+    for (record, line_result) in BufReader::new(file).lines().enumerate() {
+        let line = line_result.unwrap();
+        let codes = part1(&line, policy).and_then(|code_part1| {
+            calibrate(&line, &scanner).map(|code_part2| (code_part1, code_part2))
+        });
+        match codes {
+            Ok((code_part1, code_part2)) => {
+                total_part1 += code_part1 as u64;
+                total_part2 += code_part2 as u64;
+                if trace.enabled() {
+                    // Can't fail, calibrate already scanned the line with the same policy
+                    let tokens = scanner.first_and_last(&line).unwrap();
+                    trace.record(
+                        record + 1,
+                        "part2",
+                        vec![
+                            (
+                                "first",
+                                tokens.map_or("", |(first, _)| first.text(&line)).into(),
+                            ),
+                            (
+                                "last",
+                                tokens.map_or("", |(_, last)| last.text(&line)).into(),
+                            ),
+                            ("value", code_part2.into()),
+                        ],
+                    );
                 }
-                5 | _ => {
-                    if let Some(n) = check3lettered(&line[(i - 2)..(i + 1)]) {
-                        code += n;
-                        break;
-                    }
-                    if let Some(n) = check4lettered(&line[(i - 3)..(i + 1)]) {
-                        code += n;
-                        break;
-                    }
-                    if let Some(n) = check5lettered(&line[(i - 4)..(i + 1)]) {
-                        code += n;
-                        break;
-                    }
+                if audit_path.is_some() {
+                    // Can't fail, part2 already scanned the line with the same policy
+                    let tokens = scanner.tokens(&line).unwrap();
+                    audit.add(record + 1, &line, &tokens, code_part1, code_part2);
                 }
-            };
+            }
+            Err(e) => {
+                println!("Line {}: {}", record + 1, e);
+                process::exit(1);
+            }
         }
     }
 
-    code
-}
+    println!("Result part1: {}", total_part1);
+    println!("Result part2: {}", total_part2);
 
-fn main() {
-    let file = File::open("./input.txt").unwrap();
-    let mut total_part1: u32 = 0;
-    let mut total_part2: u32 = 0;
-    for line_result in BufReader::new(file).lines() {
-        total_part1 += part1(line_result.as_ref().unwrap());
-        total_part2 += part2(line_result.as_ref().unwrap());
+    trace.write(&mut io::stderr().lock()).unwrap();
+    if let Some(path) = audit_path {
+        let mut out = BufWriter::new(File::create(&path).unwrap());
+        audit
+            .write(audit::Format::from_path(&path), &mut out)
+            .unwrap();
     }
+}
 
-    println!("Result part1: {}", total_part1);
-    println!("Result part2: {}", total_part2);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mixed_script_lines() {
+        let scanner = Scanner::english();
+        for (line, code_part1, code_part2) in [
+            ("a1b2c3", 13, 13),
+            ("żółw7jeden", 77, 77),
+            ("日本one語٣2ü", 22, 12),
+            ("٣x", 0, 0),
+            ("Ⅻ½x4", 44, 44),
+        ] {
+            assert_eq!(part1(line, DigitPolicy::Ignore), Ok(code_part1), "{}", line);
+            assert_eq!(calibrate(line, &scanner), Ok(code_part2), "{}", line);
+        }
+
+        assert_eq!(part1("٣x5", DigitPolicy::Unicode), Ok(35));
+        assert!(part1("٣x5", DigitPolicy::Error).is_err());
+    }
 }
@@ -0,0 +1,213 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    thread,
+};
+
+use crate::digits::{DigitPolicy, NonAsciiDigit};
+use crate::scanner::Scanner;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub part1: u64,
+    pub part2: u64,
+    pub lines: usize,
+}
+
+#[derive(Debug)]
+pub enum ChunkError {
+    Io(io::Error),
+    /// `line` is counted from the start of the file, like the serial path does.
+    Digit {
+        line: usize,
+        error: NonAsciiDigit,
+    },
+}
+
+impl From<io::Error> for ChunkError {
+    fn from(error: io::Error) -> ChunkError {
+        ChunkError::Io(error)
+    }
+}
+
+/// Splits the file into `count` byte ranges that each start at the beginning of a line.
+fn chunk_bounds(path: &str, count: usize) -> io::Result<Vec<(u64, u64)>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut starts: Vec<u64> = vec![0];
+    for i in 1..count as u64 {
+        let guess = (len * i / count as u64).max(*starts.last().unwrap());
+        if guess == 0 || guess >= len {
+            continue;
+        }
+        // A line starting right at the guess belongs to this chunk, so look from the byte before
+        file.seek(SeekFrom::Start(guess - 1))?;
+        let mut skipped: Vec<u8> = vec![];
+        let read = BufReader::new(&mut file).read_until(b'\n', &mut skipped)?;
+        let start = guess - 1 + read as u64;
+        if start < len && start > *starts.last().unwrap() {
+            starts.push(start);
+        }
+    }
+
+    let ends = starts.iter().skip(1).copied().chain([len]);
+    Ok(starts.iter().copied().zip(ends).collect())
+}
+
+/// Sums one byte range line by line, stopping at the first line the policy rejects. Line numbers
+/// in errors are relative to the start of the chunk.
+fn sum_chunk(
+    path: &str,
+    (start, end): (u64, u64),
+    scanner: &Scanner,
+    policy: DigitPolicy,
+) -> Result<Totals, ChunkError> {
+    let mut totals = Totals::default();
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut reader = BufReader::new(file.take(end - start));
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(totals);
+        }
+        // Same trimming as `BufRead::lines`
+        let text = line.strip_suffix('\n').unwrap_or(&line);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+
+        let codes = crate::part1(text, policy).and_then(|code_part1| {
+            crate::calibrate(text, scanner).map(|code_part2| (code_part1, code_part2))
+        });
+        match codes {
+            Ok((code_part1, code_part2)) => {
+                totals.part1 += code_part1 as u64;
+                totals.part2 += code_part2 as u64;
+                totals.lines += 1;
+            }
+            Err(error) => {
+                let line = totals.lines + 1;
+                return Err(ChunkError::Digit { line, error });
+            }
+        }
+    }
+}
+
+/// Sums the file on `threads` workers, each streaming its own newline-aligned chunk, so memory
+/// stays at one line buffer per worker whatever the file size.
+pub fn sum(
+    path: &str,
+    scanner: &Scanner,
+    policy: DigitPolicy,
+    threads: usize,
+) -> Result<Totals, ChunkError> {
+    let bounds = chunk_bounds(path, threads.max(1))?;
+    let results: Vec<Result<Totals, ChunkError>> = thread::scope(|scope| {
+        let handles: Vec<_> = bounds
+            .iter()
+            .map(|bounds| scope.spawn(move || sum_chunk(path, *bounds, scanner, policy)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    // Chunks are merged in file order, so the first error reported is the first one in the file
+    let mut totals = Totals::default();
+    for result in results {
+        match result {
+            Ok(chunk) => {
+                totals.part1 += chunk.part1;
+                totals.part2 += chunk.part2;
+                totals.lines += chunk.lines;
+            }
+            Err(ChunkError::Digit { line, error }) => {
+                return Err(ChunkError::Digit {
+                    line: totals.lines + line,
+                    error,
+                });
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{env, fs, process};
+
+    fn serial(text: &str, scanner: &Scanner, policy: DigitPolicy) -> Totals {
+        let mut totals = Totals::default();
+        for line in text.lines() {
+            totals.part1 += crate::part1(line, policy).unwrap() as u64;
+            totals.part2 += crate::calibrate(line, scanner).unwrap() as u64;
+            totals.lines += 1;
+        }
+        totals
+    }
+
+    fn temp_file(name: &str, text: &str) -> String {
+        let path = env::temp_dir().join(format!("problem_1_{}_{}", process::id(), name));
+        fs::write(&path, text).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_matches_serial() {
+        let scanner = Scanner::english();
+        let lines = [
+            "two1nine",
+            "",
+            "eightwothree\r",
+            "abcone2threexyz",
+            "xtwone3four",
+            "4nineeightseven2",
+            "zoneight234",
+            "7pqrstsixteen",
+            "żółw7jeden",
+            "no digits here",
+        ];
+        let text: String = (0..50)
+            .flat_map(|i| lines.iter().cycle().skip(i).take(i % 7 + 1))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let path = temp_file("serial", &text);
+
+        let expected = serial(&text, &scanner, DigitPolicy::Ignore);
+        for threads in [1, 2, 3, 7, 16, 1000] {
+            let totals = sum(&path, &scanner, DigitPolicy::Ignore, threads).unwrap();
+            assert_eq!(totals, expected, "{} threads", threads);
+        }
+        fs::remove_file(path).unwrap();
+
+        // Without a trailing newline the last line still counts
+        let path = temp_file("unterminated", text.strip_suffix('\n').unwrap());
+        assert_eq!(
+            sum(&path, &scanner, DigitPolicy::Ignore, 4).unwrap(),
+            expected
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_error_line_number() {
+        let scanner = Scanner::english().with_policy(DigitPolicy::Error);
+        let text = format!("{}x٣1\n{}", "1abc\n".repeat(40), "2\n".repeat(40));
+        let path = temp_file("error", &text);
+        for threads in [1, 3, 8] {
+            match sum(&path, &scanner, DigitPolicy::Error, threads) {
+                Err(ChunkError::Digit { line, error }) => {
+                    assert_eq!(line, 41);
+                    assert_eq!(error.start, 1);
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::digits::{DigitPolicy, NonAsciiDigit};
+
+pub const ENGLISH_DIGITS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub value: u32,
+    /// Byte offsets into the scanned line.
+    pub start: usize,
+    pub end: usize,
+    pub spelled: bool,
+}
+
+impl Token {
+    pub fn leading_digit(&self) -> u32 {
+        let mut value = self.value;
+        while value >= 10 {
+            value /= 10;
+        }
+        value
+    }
+
+    pub fn trailing_digit(&self) -> u32 {
+        self.value % 10
+    }
+
+    pub fn text<'a>(&self, line: &'a str) -> &'a str {
+        &line[self.start..self.end]
+    }
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    next: HashMap<char, usize>,
+    fail: usize,
+    /// Indices into `Scanner::words` of every word ending at this node, including the ones
+    /// reachable through the fail links.
+    outputs: Vec<usize>,
+}
+
+/// Aho-Corasick automaton over a word -> value table, so every spelled digit in a line is found in
+/// one pass, overlaps included ("twone" yields both "two" and "one").
+#[derive(Debug)]
+pub struct Scanner {
+    nodes: Vec<Node>,
+    words: Vec<(String, u32)>,
+    policy: DigitPolicy,
+}
+
+impl Scanner {
+    pub fn new(table: &[(&str, u32)]) -> Scanner {
+        let mut scanner = Scanner {
+            nodes: vec![Node::default()],
+            words: vec![],
+            policy: DigitPolicy::Ignore,
+        };
+
+        for (word, value) in table.iter().filter(|(word, _)| !word.is_empty()) {
+            let mut node = 0;
+            for c in word.chars() {
+                node = match scanner.nodes[node].next.get(&c) {
+                    Some(next) => *next,
+                    None => {
+                        scanner.nodes.push(Node::default());
+                        let next = scanner.nodes.len() - 1;
+                        scanner.nodes[node].next.insert(c, next);
+                        next
+                    }
+                };
+            }
+            scanner.nodes[node].outputs.push(scanner.words.len());
+            scanner.words.push((word.to_string(), *value));
+        }
+
+        let mut queue: VecDeque<usize> = scanner.nodes[0].next.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let children: Vec<(char, usize)> = scanner.nodes[node]
+                .next
+                .iter()
+                .map(|(c, child)| (*c, *child))
+                .collect();
+            for (c, child) in children {
+                let mut fail = scanner.nodes[node].fail;
+                let fail = loop {
+                    if let Some(next) = scanner.nodes[fail].next.get(&c) {
+                        break *next;
+                    }
+                    if fail == 0 {
+                        break 0;
+                    }
+                    fail = scanner.nodes[fail].fail;
+                };
+                scanner.nodes[child].fail = fail;
+                let inherited = scanner.nodes[fail].outputs.clone();
+                scanner.nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        scanner
+    }
+
+    pub fn with_policy(mut self, policy: DigitPolicy) -> Scanner {
+        self.policy = policy;
+        self
+    }
+
+    pub fn english() -> Scanner {
+        Scanner::new(&ENGLISH_DIGITS)
+    }
+
+    /// Reads a table with one `word value` pair per line, blank lines and `#` comments ignored.
+    pub fn from_table(table: &str) -> Result<Scanner, String> {
+        let mut entries: Vec<(&str, u32)> = vec![];
+        for (number, line) in table.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut split = line.split_whitespace();
+            let (Some(word), Some(value), None) = (split.next(), split.next(), split.next()) else {
+                return Err(format!(
+                    "line {}: expected 'word value', got '{}'",
+                    number + 1,
+                    line
+                ));
+            };
+            let value: u32 = value
+                .parse()
+                .map_err(|_| format!("line {}: '{}' is not a number", number + 1, value))?;
+            entries.push((word, value));
+        }
+        Ok(Scanner::new(&entries))
+    }
+
+    /// Every digit and word token in the line, ordered by start position. Tokens starting at the
+    /// same position are ordered longest first.
+    pub fn tokens(&self, line: &str) -> Result<Vec<Token>, NonAsciiDigit> {
+        let mut tokens: Vec<Token> = vec![];
+        let mut node = 0;
+        for (i, c) in line.char_indices() {
+            let end = i + c.len_utf8();
+            if let Some(value) = self.policy.digit(c, i)? {
+                tokens.push(Token {
+                    value,
+                    start: i,
+                    end,
+                    spelled: false,
+                });
+            }
+
+            node = loop {
+                if let Some(next) = self.nodes[node].next.get(&c) {
+                    break *next;
+                }
+                if node == 0 {
+                    break 0;
+                }
+                node = self.nodes[node].fail;
+            };
+            for word in self.nodes[node].outputs.iter() {
+                let (word, value) = &self.words[*word];
+                tokens.push(Token {
+                    value: *value,
+                    start: end - word.len(),
+                    end,
+                    spelled: true,
+                });
+            }
+        }
+
+        tokens.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+        Ok(tokens)
+    }
+
+    pub fn first_and_last(&self, line: &str) -> Result<Option<(Token, Token)>, NonAsciiDigit> {
+        Ok(first_and_last(&self.tokens(line)?))
+    }
+}
+
+/// First and last token of a list ordered like `Scanner::tokens`.
+pub fn first_and_last(tokens: &[Token]) -> Option<(Token, Token)> {
+    let first = tokens.first()?;
+    let last_start = tokens.last()?.start;
+    let last = tokens.iter().find(|token| token.start == last_start)?;
+    Some((*first, *last))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn calibrate(scanner: &Scanner, line: &str) -> Option<u32> {
+        scanner
+            .first_and_last(line)
+            .unwrap()
+            .map(|(first, last)| first.leading_digit() * 10 + last.trailing_digit())
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let scanner = Scanner::english();
+        assert_eq!(calibrate(&scanner, "twone"), Some(21));
+        assert_eq!(calibrate(&scanner, "eightwo"), Some(82));
+        assert_eq!(calibrate(&scanner, "xtwone3four"), Some(24));
+        assert_eq!(calibrate(&scanner, "7pqrstsixteen"), Some(76));
+        assert_eq!(calibrate(&scanner, "oneight"), Some(18));
+        assert_eq!(calibrate(&scanner, "nothing"), None);
+    }
+
+    #[test]
+    fn test_custom_table() {
+        let scanner = Scanner::from_table("# german\neins 1\nzwei 2\nfünf 5\n").unwrap();
+        assert_eq!(calibrate(&scanner, "xfünfzweins"), Some(51));
+
+        let scanner = Scanner::new(&[("zero", 0), ("seven", 7), ("seventeen", 17)]);
+        assert_eq!(calibrate(&scanner, "seventeen"), Some(17));
+        assert_eq!(calibrate(&scanner, "zeroseven"), Some(7));
+
+        assert!(Scanner::from_table("one").is_err());
+        assert!(Scanner::from_table("one uno").is_err());
+    }
+
+    #[test]
+    fn test_mixed_script() {
+        let scanner = Scanner::english();
+        assert_eq!(calibrate(&scanner, "日本one語٣2ü"), Some(12));
+        assert_eq!(calibrate(&scanner, "٣ünf"), None);
+
+        let scanner = Scanner::english().with_policy(DigitPolicy::Unicode);
+        assert_eq!(calibrate(&scanner, "日本one語٣ü"), Some(13));
+        assert_eq!(calibrate(&scanner, "৭ünf"), Some(77));
+
+        let scanner = Scanner::english().with_policy(DigitPolicy::Error);
+        assert_eq!(
+            scanner.first_and_last("ab٣cd"),
+            Err(NonAsciiDigit { c: '٣', start: 2 })
+        );
+    }
+}
@@ -1,10 +1,14 @@
+mod explain;
+
 use std::{
     collections::HashSet,
     env,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader},
 };
 
+use explain::Trace;
+
 struct Galaxy {
     row: usize,
     col: usize,
@@ -41,9 +45,10 @@ impl Range {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let mut trace = Trace::new(explain::take_flag(&mut args));
     if args.len() != 2 {
-        println!("Usage: problem_11 [puzzle_input]");
+        println!("Usage: problem_11 [puzzle_input] [--explain[=jsonl]]");
         return;
     }
 
@@ -73,6 +78,24 @@ fn main() {
         });
     });
 
+    lines.iter().enumerate().for_each(|(row, line)| {
+        trace.record(
+            row + 1,
+            "row",
+            vec![
+                ("galaxies", line.matches('#').count().into()),
+                ("expanded", expanded_rows.contains(&row).into()),
+            ],
+        );
+    });
+    (0..lines.first().unwrap().len()).for_each(|col| {
+        trace.record(
+            col + 1,
+            "column",
+            vec![("expanded", expanded_cols.contains(&col).into())],
+        );
+    });
+
     let mut distance_sums: usize = 0;
     let mut distance_sums_part2: usize = 0;
     for i in 0..galaxies.len() {
@@ -107,4 +130,6 @@ fn main() {
 
     println!("Part 1: {}", distance_sums);
     println!("Part 2: {}", distance_sums_part2);
+
+    trace.write(&mut io::stderr().lock()).unwrap();
 }
@@ -1,10 +1,14 @@
+mod explain;
+
 use std::{
     collections::HashMap,
     env,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader},
 };
 
+use explain::Trace;
+
 #[derive(Debug, Clone, Copy)]
 enum Property {
     Extreme,
@@ -131,7 +135,8 @@ impl Range {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let mut trace = Trace::new(explain::take_flag(&mut args));
     let lines: Vec<String> = BufReader::new(File::open(args.get(1).unwrap()).unwrap())
         .lines()
         .map(|line_result| line_result.unwrap())
@@ -235,46 +240,35 @@ fn main() {
 
     // Part 1
     let mut result_part1: i64 = 0;
-    gears.iter().for_each(|gear| {
+    gears.iter().enumerate().for_each(|(index, gear)| {
         let mut current_workflow = String::from("in");
-        loop {
+        let mut path: Vec<String> = vec![current_workflow.clone()];
+        let accepted = loop {
             let workflow = workflows.get(&current_workflow).unwrap();
-            let mut found = false;
-            let mut exit = false;
-            for condition in workflow.0.iter() {
-                if condition.check(gear) {
-                    match condition.destination_state.as_str() {
-                        "A" => {
-                            result_part1 += gear.get_sum();
-                            exit = true;
-                            break;
-                        }
-                        "R" => {
-                            exit = true;
-                            break;
-                        }
-                        _ => {
-                            current_workflow = condition.destination_state.to_string();
-                            found = true;
-                            break;
-                        }
-                    };
-                }
-            }
-            if exit {
-                break;
-            }
-            if !found {
-                match workflow.1.as_str() {
-                    "A" => {
-                        result_part1 += gear.get_sum();
-                        break;
-                    }
-                    "R" => break,
-                    _ => current_workflow = workflow.1.to_string(),
-                };
-            }
+            let destination = workflow
+                .0
+                .iter()
+                .find(|condition| condition.check(gear))
+                .map_or(&workflow.1, |condition| &condition.destination_state);
+            path.push(destination.clone());
+            match destination.as_str() {
+                "A" => break true,
+                "R" => break false,
+                _ => current_workflow = destination.clone(),
+            };
+        };
+        if accepted {
+            result_part1 += gear.get_sum();
         }
+        trace.record(
+            index + 1,
+            "part",
+            vec![
+                ("path", path.join(" -> ").into()),
+                ("accepted", accepted.into()),
+                ("rating", gear.get_sum().into()),
+            ],
+        );
     });
     println!("Result part1: {}", result_part1);
 
@@ -330,4 +324,6 @@ fn main() {
     }
 
     println!("Result part2: {}", result_part2);
+
+    trace.write(&mut io::stderr().lock()).unwrap();
 }
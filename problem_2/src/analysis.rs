@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+
+use crate::game::{CubeSet, Game};
+use crate::query;
+
+/// `ln(n choose k)`, negative infinity when `k > n`.
+pub fn ln_binomial(n: u64, k: u64) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    let k = k.min(n - k);
+    (1..=k)
+        .map(|i| ((n - k + i) as f64).ln() - (i as f64).ln())
+        .sum()
+}
+
+/// Log-probability of pulling exactly this draw out of the bag in one go, without replacement
+/// (the multivariate hypergeometric distribution).
+pub fn draw_log_probability(draw: &CubeSet, bag: &CubeSet) -> f64 {
+    // Counts are u32 each, their sum can be larger
+    let bag_total: u64 = bag.counts.values().map(|count| *count as u64).sum();
+    let draw_total: u64 = draw.counts.values().map(|count| *count as u64).sum();
+    let colours: BTreeSet<&String> = draw.counts.keys().chain(bag.counts.keys()).collect();
+    // Both logs below would be -inf, and -inf - -inf is NaN rather than impossible
+    if draw_total > bag_total
+        || colours
+            .iter()
+            .any(|colour| draw.get(colour) > bag.get(colour))
+    {
+        return f64::NEG_INFINITY;
+    }
+
+    let ways: f64 = colours
+        .iter()
+        .map(|colour| ln_binomial(bag.get(colour) as u64, draw.get(colour) as u64))
+        .sum();
+    ways - ln_binomial(bag_total, draw_total)
+}
+
+/// Cubes go back in the bag between draws, so the draws of a game are independent.
+pub fn game_log_likelihood(game: &Game, bag: &CubeSet) -> f64 {
+    game.draws
+        .iter()
+        .map(|draw| draw_log_probability(draw, bag))
+        .sum()
+}
+
+pub fn log_likelihood(games: &[Game], bag: &CubeSet) -> f64 {
+    games
+        .iter()
+        .map(|game| game_log_likelihood(game, bag))
+        .sum()
+}
+
+/// Every bag of `total` cubes over `colours` holding at least the `minimum` counts.
+pub fn compositions(colours: &[String], total: u32, minimum: &CubeSet) -> Vec<CubeSet> {
+    fn fill(
+        colours: &[String],
+        remaining: u32,
+        minimum: &CubeSet,
+        bag: &mut CubeSet,
+        bags: &mut Vec<CubeSet>,
+    ) {
+        let Some((colour, rest)) = colours.split_first() else {
+            if remaining == 0 {
+                bags.push(bag.clone());
+            }
+            return;
+        };
+        let reserved: u32 = rest.iter().map(|colour| minimum.get(colour)).sum();
+        let low = minimum.get(colour);
+        if rest.is_empty() {
+            if remaining >= low {
+                bag.counts.insert(colour.clone(), remaining);
+                fill(rest, 0, minimum, bag, bags);
+            }
+            return;
+        }
+        for count in low..=remaining.saturating_sub(reserved) {
+            bag.counts.insert(colour.clone(), count);
+            fill(rest, remaining - count, minimum, bag, bags);
+        }
+        bag.counts.remove(colour);
+    }
+
+    let mut bags: Vec<CubeSet> = vec![];
+    fill(colours, total, minimum, &mut CubeSet::default(), &mut bags);
+    bags
+}
+
+/// Candidate bags with their log-likelihood over all games, most likely first.
+pub fn rank(games: &[Game], candidates: Vec<CubeSet>) -> Vec<(CubeSet, f64)> {
+    let mut ranked: Vec<(CubeSet, f64)> = candidates
+        .into_iter()
+        .map(|bag| {
+            let likelihood = log_likelihood(games, &bag);
+            (bag, likelihood)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked
+}
+
+/// Ranks every bag of `total` cubes in which all games are possible.
+pub fn rank_by_total(games: &[Game], total: u32) -> Vec<(CubeSet, f64)> {
+    let minimum = query::max_seen(games);
+    let colours: Vec<String> = minimum.counts.keys().cloned().collect();
+    rank(games, compositions(&colours, total, &minimum))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_draw_probability() {
+        assert!(close(ln_binomial(5, 2), 10_f64.ln()));
+        assert_eq!(ln_binomial(2, 3), f64::NEG_INFINITY);
+
+        let bag = CubeSet::parse_limits("red=2,blue=1").unwrap();
+        let draw = CubeSet::parse("1 red, 1 blue").unwrap();
+        assert!(close(draw_log_probability(&draw, &bag).exp(), 2.0 / 3.0));
+        let draw = CubeSet::parse("2 blue").unwrap();
+        assert_eq!(draw_log_probability(&draw, &bag), f64::NEG_INFINITY);
+        let draw = CubeSet::parse("1 green").unwrap();
+        assert_eq!(draw_log_probability(&draw, &bag), f64::NEG_INFINITY);
+        // More cubes than the whole bag holds
+        let draw = CubeSet::parse("4 red").unwrap();
+        assert_eq!(draw_log_probability(&draw, &bag), f64::NEG_INFINITY);
+        let tiny = CubeSet::parse_limits("red=1,green=1,blue=1").unwrap();
+        let game = Game::parse("Game 1: 3 blue, 4 red; 1 red").unwrap();
+        assert_eq!(game_log_likelihood(&game, &tiny), f64::NEG_INFINITY);
+        // Bag total past u32::MAX
+        let huge = CubeSet::parse_limits("red=4000000000,green=13,blue=4000000000").unwrap();
+        let draw = CubeSet::parse("1 red, 1 blue").unwrap();
+        assert!((draw_log_probability(&draw, &huge).exp() - 0.5).abs() < 1e-6);
+
+        let game = Game::parse("Game 1: 1 red, 1 blue; 1 red").unwrap();
+        assert!(close(
+            game_log_likelihood(&game, &bag).exp(),
+            2.0 / 3.0 * 2.0 / 3.0
+        ));
+    }
+
+    #[test]
+    fn test_rank() {
+        let games = vec![
+            Game::parse("Game 1: 3 red; 1 blue").unwrap(),
+            Game::parse("Game 2: 2 red, 1 blue; 3 red").unwrap(),
+        ];
+        let colours = vec!["blue".to_string(), "red".to_string()];
+        let minimum = query::max_seen(&games);
+        let bags = compositions(&colours, 6, &minimum);
+        assert_eq!(bags.len(), 3);
+        assert!(bags.iter().all(|bag| bag.counts.values().sum::<u32>() == 6));
+
+        let ranked = rank_by_total(&games, 6);
+        assert_eq!(ranked.len(), 3);
+        assert_eq!(ranked[0].0, CubeSet::parse_limits("red=5,blue=1").unwrap());
+        assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+}
@@ -0,0 +1,134 @@
+use std::{collections::BTreeMap, fmt};
+
+/// Cube count per colour, for a single draw or for the content of a bag.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CubeSet {
+    pub counts: BTreeMap<String, u32>,
+}
+
+impl CubeSet {
+    /// Parses `3 blue, 4 red`.
+    pub fn parse(s: &str) -> Result<CubeSet, String> {
+        let mut cube_set = CubeSet::default();
+        for cube_data in s.split(',').map(|data| data.trim()) {
+            let Some((count, colour)) = cube_data.split_once(' ') else {
+                return Err(format!("expected '<count> <colour>', got '{}'", cube_data));
+            };
+            let count: u32 = count
+                .parse()
+                .map_err(|_| format!("'{}' is not a cube count", count))?;
+            *cube_set
+                .counts
+                .entry(colour.trim().to_string())
+                .or_insert(0) += count;
+        }
+        Ok(cube_set)
+    }
+
+    /// Parses `red=12,green=13,blue=14`.
+    pub fn parse_limits(s: &str) -> Result<CubeSet, String> {
+        let mut cube_set = CubeSet::default();
+        for limit in s.split(',').map(|limit| limit.trim()) {
+            let Some((colour, count)) = limit.split_once('=') else {
+                return Err(format!("expected '<colour>=<count>', got '{}'", limit));
+            };
+            let count: u32 = count
+                .parse()
+                .map_err(|_| format!("'{}' is not a cube count", count))?;
+            cube_set.counts.insert(colour.to_string(), count);
+        }
+        Ok(cube_set)
+    }
+
+    pub fn get(&self, colour: &str) -> u32 {
+        *self.counts.get(colour).unwrap_or(&0)
+    }
+
+    /// Raises every count to at least the one in `other`.
+    pub fn cover(&mut self, other: &CubeSet) {
+        for (colour, count) in other.counts.iter() {
+            let own_count = self.counts.entry(colour.clone()).or_insert(0);
+            if count > own_count {
+                *own_count = *count;
+            }
+        }
+    }
+}
+
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cubes: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(colour, count)| format!("{} {}", count, colour))
+            .collect();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    pub draws: Vec<CubeSet>,
+}
+
+impl Game {
+    /// Parses `Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green`.
+    pub fn parse(line: &str) -> Result<Game, String> {
+        let Some((header, draws)) = line.split_once(':') else {
+            return Err(format!("missing ':' in '{}'", line));
+        };
+        let id = header
+            .trim()
+            .strip_prefix("Game")
+            .map(|id| id.trim())
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| format!("expected 'Game <id>', got '{}'", header))?;
+        let draws = draws
+            .split(';')
+            .map(CubeSet::parse)
+            .collect::<Result<Vec<CubeSet>, String>>()
+            .map_err(|e| format!("game {}: {}", id, e))?;
+        Ok(Game { id, draws })
+    }
+
+    /// Whether every draw fits in the bag, colours missing from the bag having no cubes.
+    pub fn is_possible(&self, bag: &CubeSet) -> bool {
+        self.draws.iter().all(|draw| {
+            draw.counts
+                .iter()
+                .all(|(colour, count)| *count <= bag.get(colour))
+        })
+    }
+}
+
+/// Smallest bag in which every draw of the game fits.
+pub fn minimal_bag(game: &Game) -> CubeSet {
+    let mut required = CubeSet::default();
+    for draw in game.draws.iter() {
+        required.cover(draw);
+    }
+    required
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_any_colour() {
+        let game = Game::parse("Game 12: 3 blue, 4 pink; 1 red, 2 green; 7 pink").unwrap();
+        assert_eq!(game.id, 12);
+        assert_eq!(game.draws.len(), 3);
+        assert_eq!(game.draws[0].get("pink"), 4);
+        assert_eq!(game.draws[1].get("pink"), 0);
+
+        let bag = CubeSet::parse_limits("red=1,green=2,blue=3").unwrap();
+        assert!(!game.is_possible(&bag));
+        let bag = CubeSet::parse_limits("red=1,green=2,blue=3,pink=7").unwrap();
+        assert!(game.is_possible(&bag));
+
+        assert!(Game::parse("Gaem 1: 3 blue").is_err());
+        assert!(Game::parse("Game 1: blue").is_err());
+    }
+}
@@ -1,112 +1,154 @@
+mod analysis;
+mod game;
+mod query;
+
 use std::{
+    collections::BTreeSet,
+    env,
     fs::File,
     io::{BufRead, BufReader},
+    process,
 };
 
-const MAX_RED: u32 = 12;
-const MAX_GREEN: u32 = 13;
-const MAX_BLUE: u32 = 14;
-
-fn part1(line: &String) -> u32 {
-    let colon_split: Vec<&str> = line.split(":").collect();
-    let game_id: u32 = (&(colon_split.get(0).unwrap())[5..]).parse().unwrap();
-    let subsets: Vec<&str> = colon_split.get(1).unwrap().split(";").collect();
-
-    for subset in subsets.iter() {
-        let mut cubes_data: Vec<&str> = subset.split(",").collect();
-        cubes_data.iter_mut().for_each(|data| {
-            *data = data.trim();
-        });
-
-        for cube_data in cubes_data.iter() {
-            let cube_split: Vec<&str> = cube_data.split(" ").collect();
-            let count: u32 = cube_split.get(0).unwrap().parse().unwrap();
-            let color = cube_split.get(1).unwrap();
-            match *color {
-                "red" => {
-                    if count > MAX_RED {
-                        println!("Game {game_id} not possible");
-                        return 0;
-                    }
-                }
-                "green" => {
-                    if count > MAX_GREEN {
-                        println!("Game {game_id} not possible");
-                        return 0;
-                    }
-                }
-                "blue" => {
-                    if count > MAX_BLUE {
-                        println!("Game {game_id} not possible");
-                        return 0;
-                    }
-                }
-                _ => unreachable!("What is this color?"),
-            };
-        }
+use game::{CubeSet, Game};
+
+const DEFAULT_BAG: &str = "red=12,green=13,blue=14";
+
+fn part1(game: &Game, bag: &CubeSet) -> u32 {
+    if !game.is_possible(bag) {
+        println!("Game {} not possible", game.id);
+        return 0;
     }
 
-    println!("Game {game_id} is possible");
-    game_id
+    println!("Game {} is possible", game.id);
+    game.id
 }
 
-fn part2(line: &String) -> u32 {
-    let colon_split: Vec<&str> = line.split(":").collect();
-    let game_id: u32 = (&(colon_split.get(0).unwrap())[5..]).parse().unwrap();
-    let subsets: Vec<&str> = colon_split.get(1).unwrap().split(";").collect();
-
-    let mut required_red: u32 = 0;
-    let mut required_green: u32 = 0;
-    let mut required_blue: u32 = 0;
-
-    for subset in subsets.iter() {
-        let mut cubes_data: Vec<&str> = subset.split(",").collect();
-        cubes_data.iter_mut().for_each(|data| {
-            *data = data.trim();
-        });
-
-        for cube_data in cubes_data.iter() {
-            let cube_split: Vec<&str> = cube_data.split(" ").collect();
-            let count: u32 = cube_split.get(0).unwrap().parse().unwrap();
-            let color = cube_split.get(1).unwrap();
-            match *color {
-                "red" => {
-                    if count > required_red {
-                        required_red = count;
-                    }
-                }
-                "green" => {
-                    if count > required_green {
-                        required_green = count;
-                    }
-                }
-                "blue" => {
-                    if count > required_blue {
-                        required_blue = count;
-                    }
-                }
-                _ => unreachable!("What is this color?"),
-            };
-        }
-    }
+fn part2(game: &Game, colours: &BTreeSet<String>) -> u32 {
+    let required = game::minimal_bag(game);
 
-    println!("Game {game_id} requires {required_red} red, {required_green} green and {required_blue} blue cubes");
+    println!("Game {} requires {}", game.id, required);
 
-    required_red * required_blue * required_green
+    // A colour seen in other games but never in this one requires no cubes
+    colours.iter().map(|colour| required.get(colour)).product()
 }
 
-fn main() {
+fn read_games() -> Vec<Game> {
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
 
+    reader
+        .lines()
+        .map(|line_result| line_result.unwrap())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Game::parse(&line).unwrap())
+        .collect()
+}
+
+fn solve(games: &[Game], bag: &CubeSet) {
+    let colours: BTreeSet<String> = games
+        .iter()
+        .flat_map(|game| game.draws.iter())
+        .flat_map(|draw| draw.counts.keys().cloned())
+        .collect();
+
     let mut total_part1: u32 = 0;
     let mut total_part2: u32 = 0;
 
-    for line_result in reader.lines() {
-        total_part1 += part1(line_result.as_ref().unwrap());
-        total_part2 += part2(line_result.as_ref().unwrap());
+    for game in games.iter() {
+        total_part1 += part1(game, bag);
+        total_part2 += part2(game, &colours);
     }
 
     println!("Result part1: {}", total_part1);
     println!("Result part2: {}", total_part2);
 }
+
+fn parse_bag(bag: Option<&String>) -> CubeSet {
+    // The bag can be given as `colour=count` pairs, like `red=12,green=13,blue=14`
+    CubeSet::parse_limits(bag.map_or(DEFAULT_BAG, |bag| bag.as_str())).unwrap_or_else(|e| {
+        println!("Invalid bag: {}", e);
+        process::exit(1);
+    })
+}
+
+fn print_usage() {
+    println!("Usage: problem_2 [colour=count,...]");
+    println!("       problem_2 violations [colour=count,...]");
+    println!("       problem_2 max");
+    println!("       problem_2 bag-for [game_id,...]");
+    println!("       problem_2 likelihood [colour=count,...]");
+    println!("       problem_2 rank [total_cubes] [top]");
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("violations") => {
+            let bag = parse_bag(args.get(2));
+            for game in read_games().iter() {
+                for violation in query::violations(game, &bag) {
+                    println!(
+                        "Game {} draw {}: {} {} is {} over",
+                        game.id,
+                        violation.draw + 1,
+                        violation.count,
+                        violation.colour,
+                        violation.excess
+                    );
+                }
+            }
+        }
+        Some("max") => println!("Most cubes seen: {}", query::max_seen(&read_games())),
+        Some("bag-for") => {
+            let Some(targets) = args
+                .get(2)
+                .map(|ids| ids.split(',').map(|id| id.trim().parse::<u32>()).collect())
+                .and_then(|ids: Result<BTreeSet<u32>, _>| ids.ok())
+            else {
+                print_usage();
+                process::exit(1);
+            };
+            match query::bag_for_exactly(&read_games(), &targets) {
+                Ok(bag) => println!("Smallest bag: {}", bag),
+                Err(e) => println!("{}", e),
+            }
+        }
+        Some("likelihood") => {
+            let bag = parse_bag(args.get(2));
+            let games = read_games();
+            for game in games.iter() {
+                let likelihood = analysis::game_log_likelihood(game, &bag);
+                match likelihood == f64::NEG_INFINITY {
+                    true => println!("Game {}: impossible with this bag", game.id),
+                    false => println!(
+                        "Game {}: ln(p) = {:.4}, p = {:e}",
+                        game.id,
+                        likelihood,
+                        likelihood.exp()
+                    ),
+                }
+            }
+            match analysis::log_likelihood(&games, &bag) {
+                f64::NEG_INFINITY => println!("All games: impossible with this bag"),
+                likelihood => println!("All games: ln(p) = {:.4}", likelihood),
+            }
+        }
+        Some("rank") => {
+            let Some(total) = args.get(2).and_then(|total| total.parse::<u32>().ok()) else {
+                print_usage();
+                process::exit(1);
+            };
+            let top: usize = args.get(3).and_then(|top| top.parse().ok()).unwrap_or(10);
+            let ranked = analysis::rank_by_total(&read_games(), total);
+            if ranked.is_empty() {
+                println!("No bag of {} cubes fits every game", total);
+            }
+            for (i, (bag, likelihood)) in ranked.iter().take(top).enumerate() {
+                println!("{:>3}. {} (ln(p) = {:.4})", i + 1, bag, likelihood);
+            }
+        }
+        Some(arg) if !arg.contains('=') => print_usage(),
+        _ => solve(&read_games(), &parse_bag(args.get(1))),
+    }
+}
@@ -0,0 +1,149 @@
+use std::{collections::BTreeSet, fmt};
+
+use crate::game::{minimal_bag, CubeSet, Game};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Index of the draw within the game.
+    pub draw: usize,
+    pub colour: String,
+    pub count: u32,
+    pub excess: u32,
+}
+
+/// Every draw count that doesn't fit in the bag, empty if the game is possible.
+pub fn violations(game: &Game, bag: &CubeSet) -> Vec<Violation> {
+    game.draws
+        .iter()
+        .enumerate()
+        .flat_map(|(draw, cube_set)| {
+            cube_set
+                .counts
+                .iter()
+                .filter(|(colour, count)| **count > bag.get(colour))
+                .map(move |(colour, count)| Violation {
+                    draw,
+                    colour: colour.clone(),
+                    count: *count,
+                    excess: count - bag.get(colour),
+                })
+        })
+        .collect()
+}
+
+/// Largest count drawn for each colour over all games.
+pub fn max_seen(games: &[Game]) -> CubeSet {
+    let mut max = CubeSet::default();
+    for game in games.iter() {
+        max.cover(&minimal_bag(game));
+    }
+    max
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BagError {
+    /// Target IDs that no game has.
+    UnknownGames(Vec<u32>),
+    /// Games that fit in any bag the targets fit in.
+    AlsoPossible(Vec<u32>),
+}
+
+impl fmt::Display for BagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BagError::UnknownGames(ids) => write!(f, "There are no games {:?}", ids),
+            BagError::AlsoPossible(ids) => write!(
+                f,
+                "No bag excludes games {:?}, they fit in any bag the targets fit in",
+                ids
+            ),
+        }
+    }
+}
+
+/// Smallest bag with which exactly the `targets` games are possible. Any bag that allows the
+/// targets holds at least this one, so if another game fits in it no bag can exclude that game.
+pub fn bag_for_exactly(games: &[Game], targets: &BTreeSet<u32>) -> Result<CubeSet, BagError> {
+    let unknown: Vec<u32> = targets
+        .iter()
+        .filter(|id| !games.iter().any(|game| game.id == **id))
+        .copied()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(BagError::UnknownGames(unknown));
+    }
+
+    let target_games: Vec<Game> = games
+        .iter()
+        .filter(|game| targets.contains(&game.id))
+        .cloned()
+        .collect();
+    let bag = max_seen(&target_games);
+
+    let also_possible: Vec<u32> = games
+        .iter()
+        .filter(|game| !targets.contains(&game.id) && game.is_possible(&bag))
+        .map(|game| game.id)
+        .collect();
+    match also_possible.is_empty() {
+        true => Ok(bag),
+        false => Err(BagError::AlsoPossible(also_possible)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn games() -> Vec<Game> {
+        [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+            "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+            "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        ]
+        .iter()
+        .map(|line| Game::parse(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_violations() {
+        let games = games();
+        let bag = CubeSet::parse_limits("red=12,green=13,blue=14").unwrap();
+        assert!(violations(&games[0], &bag).is_empty());
+        assert_eq!(
+            violations(&games[2], &bag),
+            vec![Violation {
+                draw: 0,
+                colour: "red".to_string(),
+                count: 20,
+                excess: 8
+            }]
+        );
+        assert_eq!(violations(&games[3], &bag).len(), 2);
+    }
+
+    #[test]
+    fn test_bag_queries() {
+        let games = games();
+        assert_eq!(
+            max_seen(&games),
+            CubeSet::parse_limits("blue=15,green=13,red=20").unwrap()
+        );
+
+        let bag = bag_for_exactly(&games, &BTreeSet::from([1, 2, 5])).unwrap();
+        assert_eq!(bag, CubeSet::parse_limits("blue=6,green=3,red=6").unwrap());
+
+        // Games 1, 2 and 5 fit in anything game 3 fits in
+        assert_eq!(
+            bag_for_exactly(&games, &BTreeSet::from([3])),
+            Err(BagError::AlsoPossible(vec![1, 2, 5]))
+        );
+        assert_eq!(
+            bag_for_exactly(&games, &BTreeSet::from([2, 7, 9])),
+            Err(BagError::UnknownGames(vec![7, 9]))
+        );
+    }
+}
@@ -1,241 +1,116 @@
+mod render;
+mod rules;
+mod schematic;
+mod stream;
+
 use std::{
-    collections::{HashMap, HashSet},
-    fs::File,
-    io::{BufRead, BufReader},
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
 };
 
-type Gear = (usize, usize);
+use rules::{Count, Rule};
+use schematic::Schematic;
 
-fn is_symbol(ch: &char) -> bool {
-    !ch.is_numeric() && *ch != '.'
+fn part1(schematic: &Schematic) {
+    let total: u64 = schematic.part_numbers().map(|number| number.value).sum();
+    println!("Part 1: {}", total);
 }
 
-fn is_gear(ch: &char) -> bool {
-    *ch == '*'
+fn part2(schematic: &Schematic) {
+    let gears = Rule::parse("*:2:product").unwrap();
+    println!("Part 2: {}", gears.apply(schematic));
 }
 
-fn check_for_symbol(
-    machine: &Vec<Vec<char>>,
-    row: usize,
-    col: usize,
-    rows: usize,
-    cols: usize,
-) -> bool {
-    // Up
-    if row != 0 {
-        let up_row = row - 1;
-        if is_symbol(&machine[up_row][col]) {
-            return true;
-        }
-        // Up right
-        if col != cols - 1 && is_symbol(&machine[up_row][col + 1]) {
-            return true;
-        }
-        // Up left
-        if col != 0 && is_symbol(&machine[up_row][col - 1]) {
-            return true;
-        }
-    }
-
-    // Down
-    if row != rows - 1 {
-        let down_row = row + 1;
-        if is_symbol(&machine[down_row][col]) {
-            return true;
-        }
-        // Down right
-        if col != cols - 1 && is_symbol(&machine[down_row][col + 1]) {
-            return true;
-        }
-        // Down left
-        if col != 0 && is_symbol(&machine[down_row][col - 1]) {
-            return true;
-        }
-    }
-
-    // Left
-    if col != 0 && is_symbol(&machine[row][col - 1]) {
-        return true;
-    }
-
-    // Right
-    if col != cols - 1 && is_symbol(&machine[row][col + 1]) {
-        return true;
-    }
-
-    false
+fn print_usage() {
+    println!("Usage: problem_3");
+    println!("       problem_3 adjacent [symbol]");
+    println!("       problem_3 rule [symbols:count:reducer]...");
+    println!("       problem_3 unusual [expected_count]");
+    println!("       problem_3 render [--html output_file]");
+    println!("       problem_3 stream [puzzle_input_file]");
 }
 
-fn get_surrounding_gears(
-    machine: &Vec<Vec<char>>,
-    row: usize,
-    col: usize,
-    rows: usize,
-    cols: usize,
-) -> Vec<Gear> {
-    let mut gears: Vec<Gear> = vec![];
-
-    // Up
-    if row != 0 {
-        let up_row = row - 1;
-        if is_gear(&machine[up_row][col]) {
-            gears.push((up_row, col));
-        }
-        // Up right
-        if col != cols - 1 && is_gear(&machine[up_row][col + 1]) {
-            gears.push((up_row, col + 1));
-        }
-        // Up left
-        if col != 0 && is_gear(&machine[up_row][col - 1]) {
-            gears.push((up_row, col - 1));
-        }
-    }
-
-    // Down
-    if row != rows - 1 {
-        let down_row = row + 1;
-        if is_gear(&machine[down_row][col]) {
-            gears.push((down_row, col));
-        }
-        // Down right
-        if col != cols - 1 && is_gear(&machine[down_row][col + 1]) {
-            gears.push((down_row, col + 1));
-        }
-        // Down left
-        if col != 0 && is_gear(&machine[down_row][col - 1]) {
-            gears.push((down_row, col - 1));
-        }
-    }
-
-    // Left
-    if col != 0 && is_gear(&machine[row][col - 1]) {
-        gears.push((row, col - 1));
-    }
-
-    // Right
-    if col != cols - 1 && is_gear(&machine[row][col + 1]) {
-        gears.push((row, col + 1));
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    // Reads stdin when no file is given and never holds more than three rows
+    if args.get(1).map(|arg| arg.as_str()) == Some("stream") {
+        let totals = match args.get(2) {
+            Some(path) => stream::solve_reader(BufReader::new(File::open(path).unwrap())),
+            None => stream::solve_reader(io::stdin().lock()),
+        }
+        .unwrap();
+        println!("Part 1: {}", totals.part1);
+        println!("Part 2: {}", totals.part2);
+        return;
     }
 
-    gears
-}
+    let file = File::open("input.txt").unwrap();
+    let reader = BufReader::new(file);
 
-fn part1(machine: &Vec<Vec<char>>, rows: usize, cols: usize) {
-    let mut total: u64 = 0;
-    let mut number = String::from("");
-    let mut found_symbol = false;
+    let reader_lines: Vec<String> = reader
+        .lines()
+        .map(|line_result| line_result.unwrap())
+        .collect();
 
-    for i in 0..rows {
-        for j in 0..cols {
-            let ch = machine[i][j];
-            if ch.is_numeric() {
-                number = format!("{}{}", number, ch);
-                if check_for_symbol(machine, i, j, rows, cols) {
-                    found_symbol = true;
-                }
-            } else {
-                if found_symbol {
-                    let n: u64 = number.parse().unwrap();
-                    total += n;
-                }
-                if number.len() > 0 {
-                    print!("({} {})", number, found_symbol);
-                }
-                number.clear();
-                found_symbol = false;
+    let schematic = Schematic::parse(&reader_lines);
+
+    match args.get(1).map(|arg| arg.as_str()) {
+        None => {
+            part1(&schematic);
+            part2(&schematic);
+        }
+        Some("adjacent") => {
+            let Some(ch) = args.get(2).and_then(|arg| arg.chars().next()) else {
+                print_usage();
+                return;
+            };
+            for number in schematic.numbers_adjacent_to(ch) {
+                println!(
+                    "{} at row {}, columns {}..{}",
+                    number.value,
+                    number.row + 1,
+                    number.col_span.start + 1,
+                    number.col_span.end
+                );
             }
         }
-        if found_symbol {
-            let n: u64 = number.parse().unwrap();
-            total += n;
-        }
-        if number.len() > 0 {
-            print!("({} {})", number, found_symbol);
-        }
-        number.clear();
-        found_symbol = false;
-        println!();
-    }
-    println!("Part 1: {}", total);
-}
-
-fn part2(machine: &Vec<Vec<char>>, rows: usize, cols: usize) {
-    let mut number = String::from("");
-    let mut gear_numbers: HashMap<Gear, Vec<u64>> = HashMap::new();
-    let mut gears: HashSet<Gear> = HashSet::new();
-
-    for i in 0..rows {
-        for j in 0..cols {
-            let ch = machine[i][j];
-            if ch.is_numeric() {
-                number = format!("{}{}", number, ch);
-                get_surrounding_gears(machine, i, j, rows, cols)
-                    .iter()
-                    .for_each(|gear| {
-                        gears.insert(*gear);
-                    });
-            } else {
-                if !number.is_empty() {
-                    let n: u64 = number.parse().unwrap();
-                    gears.iter().for_each(|gear| {
-                        if let Some(numbers) = gear_numbers.get_mut(gear) {
-                            numbers.push(n);
-                        } else {
-                            gear_numbers.insert(*gear, vec![n]);
-                        }
-                    });
+        // Rules are given like `*:2:product` or `+:>=3:sum`, see `Rule::parse`
+        Some("rule") if args.len() > 2 => {
+            for arg in args.iter().skip(2) {
+                match Rule::parse(arg) {
+                    Ok(rule) => println!("{}: {}", arg, rule.apply(&schematic)),
+                    Err(e) => println!("{}: {}", arg, e),
                 }
-                number.clear();
-                gears.clear();
             }
         }
-        if !number.is_empty() {
-            let n: u64 = number.parse().unwrap();
-            gears.iter().for_each(|gear| {
-                if let Some(numbers) = gear_numbers.get_mut(gear) {
-                    numbers.push(n);
-                } else {
-                    gear_numbers.insert(*gear, vec![n]);
+        Some("unusual") => {
+            let expected = match Count::parse(args.get(2).map_or("1-2", |arg| arg.as_str())) {
+                Ok(expected) => expected,
+                Err(e) => {
+                    println!("{}", e);
+                    return;
                 }
-            });
-        }
-        number.clear();
-        gears.clear();
-    }
-
-    let total: u64 = gear_numbers
-        .iter()
-        .map(|(_gear, numbers)| {
-            if numbers.len() == 2 {
-                return numbers.get(0).unwrap() * numbers.get(1).unwrap();
+            };
+            for (symbol, count) in rules::unusual_symbols(&schematic, expected) {
+                let symbol = schematic.symbols[symbol];
+                println!(
+                    "'{}' at row {}, column {} has {} adjacent numbers",
+                    symbol.ch,
+                    symbol.pos.0 + 1,
+                    symbol.pos.1 + 1,
+                    count
+                );
             }
-            0
-        })
-        .sum();
-
-    println!("Part 2: {}", total);
-}
-
-fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-
-    let reader_lines: Vec<String> = reader
-        .lines()
-        .map(|line_result| line_result.unwrap())
-        .collect();
-
-    let rows = reader_lines.len();
-    let cols = reader_lines.get(0).unwrap().len();
-
-    let mut machine: Vec<Vec<char>> = vec![vec![' '; cols]; rows];
-
-    for (row, line) in reader_lines.iter().enumerate() {
-        for (col, ch) in line.chars().enumerate() {
-            machine[row][col] = ch;
         }
+        Some("render") => match (args.get(2).map(|arg| arg.as_str()), args.get(3)) {
+            (None, _) => print!("{}", render::ansi(&reader_lines, &schematic)),
+            (Some("--html"), Some(path)) => {
+                fs::write(path, render::html(&reader_lines, &schematic)).unwrap()
+            }
+            _ => print_usage(),
+        },
+        Some(_) => print_usage(),
     }
-
-    part1(&machine, rows, cols);
-    part2(&machine, rows, cols);
 }
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use crate::schematic::{Pos, Schematic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    PartNumber,
+    LoneNumber,
+    /// A `*` next to exactly two numbers.
+    Gear,
+    Symbol,
+}
+
+impl Cell {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Cell::Empty => "\x1b[2m",
+            Cell::PartNumber => "\x1b[1;32m",
+            Cell::LoneNumber => "\x1b[31m",
+            Cell::Gear => "\x1b[1;30;43m",
+            Cell::Symbol => "\x1b[2;37m",
+        }
+    }
+
+    fn html_class(&self) -> &'static str {
+        match self {
+            Cell::Empty => "e",
+            Cell::PartNumber => "p",
+            Cell::LoneNumber => "n",
+            Cell::Gear => "g",
+            Cell::Symbol => "s",
+        }
+    }
+}
+
+fn classify(schematic: &Schematic) -> HashMap<Pos, Cell> {
+    let mut cells: HashMap<Pos, Cell> = HashMap::new();
+    for (index, number) in schematic.numbers.iter().enumerate() {
+        let cell = match schematic.symbols_of(index).is_empty() {
+            true => Cell::LoneNumber,
+            false => Cell::PartNumber,
+        };
+        for col in number.col_span.clone() {
+            cells.insert((number.row, col), cell);
+        }
+    }
+    for (index, symbol) in schematic.symbols.iter().enumerate() {
+        let cell = match symbol.ch == '*' && schematic.numbers_of(index).len() == 2 {
+            true => Cell::Gear,
+            false => Cell::Symbol,
+        };
+        cells.insert(symbol.pos, cell);
+    }
+    cells
+}
+
+/// Calls `emit` once per run of cells of the same kind, and with `None` at the end of each line.
+fn runs(lines: &[String], schematic: &Schematic, mut emit: impl FnMut(Option<(Cell, &str)>)) {
+    let cells = classify(schematic);
+    for (row, line) in lines.iter().enumerate() {
+        let mut run = String::new();
+        let mut run_cell = Cell::Empty;
+        for (col, ch) in line.chars().enumerate() {
+            let cell = *cells.get(&(row, col)).unwrap_or(&Cell::Empty);
+            if cell != run_cell && !run.is_empty() {
+                emit(Some((run_cell, &run)));
+                run.clear();
+            }
+            run_cell = cell;
+            run.push(ch);
+        }
+        if !run.is_empty() {
+            emit(Some((run_cell, &run)));
+        }
+        emit(None);
+    }
+}
+
+/// The schematic with ANSI colours: part numbers green, other numbers red, gears highlighted and
+/// the remaining symbols dimmed.
+pub fn ansi(lines: &[String], schematic: &Schematic) -> String {
+    let mut out = String::new();
+    runs(lines, schematic, |run| match run {
+        Some((cell, text)) => {
+            out.push_str(cell.ansi());
+            out.push_str(text);
+            out.push_str("\x1b[0m");
+        }
+        None => out.push('\n'),
+    });
+    out
+}
+
+/// Same colours as `ansi`, as a standalone HTML page.
+pub fn html(lines: &[String], schematic: &Schematic) -> String {
+    let mut out = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>Engine schematic</title>\n<style>\n",
+        "body { background: #111; color: #ccc; }\n",
+        "pre { font-family: monospace; line-height: 1.1; }\n",
+        ".e { color: #444; }\n",
+        ".p { color: #4c4; font-weight: bold; }\n",
+        ".n { color: #d44; }\n",
+        ".g { color: #111; background: #dc3; font-weight: bold; }\n",
+        ".s { color: #777; }\n",
+        "</style>\n</head>\n<body>\n<pre>\n",
+    ));
+    runs(lines, schematic, |run| match run {
+        Some((cell, text)) => {
+            out.push_str(&format!("<span class=\"{}\">", cell.html_class()));
+            for ch in text.chars() {
+                match ch {
+                    '<' => out.push_str("&lt;"),
+                    '>' => out.push_str("&gt;"),
+                    '&' => out.push_str("&amp;"),
+                    _ => out.push(ch),
+                }
+            }
+            out.push_str("</span>");
+        }
+        None => out.push('\n'),
+    });
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The puzzle example, with `#` and `$` swapped for characters HTML needs escaped.
+    fn lines() -> Vec<String> {
+        crate::schematic::example()
+            .iter()
+            .map(|line| line.replace('#', "<").replace('$', "&"))
+            .collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        let cells = classify(&Schematic::parse(&lines()));
+        assert_eq!(cells[&(0, 0)], Cell::PartNumber);
+        assert_eq!(cells[&(0, 5)], Cell::LoneNumber);
+        assert_eq!(cells[&(5, 8)], Cell::LoneNumber);
+        // Between 467 and 35, then next to 617 only
+        assert_eq!(cells[&(1, 3)], Cell::Gear);
+        assert_eq!(cells[&(4, 3)], Cell::Symbol);
+        assert_eq!(cells[&(8, 5)], Cell::Gear);
+        assert_eq!(cells[&(5, 5)], Cell::Symbol);
+        assert_eq!(cells.get(&(0, 3)), None);
+    }
+
+    #[test]
+    fn test_ansi() {
+        let lines = lines();
+        let out = ansi(&lines, &Schematic::parse(&lines));
+        assert_eq!(out.lines().count(), 10);
+        assert_eq!(
+            out.lines().next().unwrap(),
+            "\x1b[1;32m467\x1b[0m\x1b[2m..\x1b[0m\x1b[31m114\x1b[0m\x1b[2m..\x1b[0m"
+        );
+        assert!(out.contains("\x1b[1;30;43m*\x1b[0m"));
+    }
+
+    #[test]
+    fn test_html() {
+        let lines = lines();
+        let out = html(&lines, &Schematic::parse(&lines));
+        assert!(out.contains("<span class=\"s\">&lt;</span>"));
+        assert!(out.contains("<span class=\"s\">&amp;</span>"));
+        assert!(out.contains("<span class=\"n\">114</span>"));
+        assert!(out.contains("<span class=\"g\">*</span>"));
+        // Only the escaped forms make it into the page body
+        let body = &out[out.find("<pre>").unwrap()..];
+        assert!(!body.contains(">&<") && !body.contains("><<"));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::schematic::Schematic;
+
+/// Which symbols a rule applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolClass {
+    Any,
+    OneOf(Vec<char>),
+}
+
+impl SymbolClass {
+    fn parse(s: &str) -> Result<SymbolClass, String> {
+        match s {
+            "" => Err("empty symbol class".to_string()),
+            "any" => Ok(SymbolClass::Any),
+            _ => Ok(SymbolClass::OneOf(s.chars().collect())),
+        }
+    }
+
+    fn contains(&self, ch: char) -> bool {
+        match self {
+            SymbolClass::Any => true,
+            SymbolClass::OneOf(chars) => chars.contains(&ch),
+        }
+    }
+}
+
+/// How many adjacent numbers a symbol must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Count {
+    Any,
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+    Between(usize, usize),
+}
+
+impl Count {
+    /// Parses `any`, `2`, `>=3`, `<=1` or `1-2`.
+    pub fn parse(s: &str) -> Result<Count, String> {
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("'{}' is not a count", s))
+        };
+        if s == "any" {
+            Ok(Count::Any)
+        } else if let Some(n) = s.strip_prefix(">=") {
+            Ok(Count::AtLeast(number(n)?))
+        } else if let Some(n) = s.strip_prefix("<=") {
+            Ok(Count::AtMost(number(n)?))
+        } else if let Some((low, high)) = s.split_once('-') {
+            Ok(Count::Between(number(low)?, number(high)?))
+        } else {
+            Ok(Count::Exactly(number(s)?))
+        }
+    }
+
+    pub fn matches(&self, count: usize) -> bool {
+        match *self {
+            Count::Any => true,
+            Count::Exactly(n) => count == n,
+            Count::AtLeast(n) => count >= n,
+            Count::AtMost(n) => count <= n,
+            Count::Between(low, high) => low <= count && count <= high,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reducer {
+    Product,
+    Sum,
+    Max,
+    Min,
+    Count,
+}
+
+impl Reducer {
+    fn parse(s: &str) -> Result<Reducer, String> {
+        match s {
+            "product" => Ok(Reducer::Product),
+            "sum" => Ok(Reducer::Sum),
+            "max" => Ok(Reducer::Max),
+            "min" => Ok(Reducer::Min),
+            "count" => Ok(Reducer::Count),
+            _ => Err(format!("unknown reducer '{}'", s)),
+        }
+    }
+
+    /// `None` when there are no values to reduce.
+    fn reduce(&self, values: &[u64]) -> Option<u64> {
+        if values.is_empty() {
+            return None;
+        }
+        let values = values.iter().copied();
+        match self {
+            Reducer::Product => Some(values.product()),
+            Reducer::Sum => Some(values.sum()),
+            Reducer::Max => values.max(),
+            Reducer::Min => values.min(),
+            Reducer::Count => Some(values.count() as u64),
+        }
+    }
+}
+
+/// Reduces the numbers around each matching symbol, the results being summed over all symbols.
+/// Part 2 is `*:2:product`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub class: SymbolClass,
+    pub count: Count,
+    pub reducer: Reducer,
+}
+
+impl Rule {
+    /// Parses `symbols:count:reducer`, like `*:2:product`, `+:>=3:sum` or `any:any:max`.
+    pub fn parse(s: &str) -> Result<Rule, String> {
+        // Split from the right so ':' can be a symbol as well
+        let mut split = s.rsplitn(3, ':');
+        let (Some(reducer), Some(count), Some(class)) = (split.next(), split.next(), split.next())
+        else {
+            return Err(format!("expected 'symbols:count:reducer', got '{}'", s));
+        };
+        Ok(Rule {
+            class: SymbolClass::parse(class)?,
+            count: Count::parse(count)?,
+            reducer: Reducer::parse(reducer)?,
+        })
+    }
+
+    pub fn apply(&self, schematic: &Schematic) -> u64 {
+        schematic
+            .symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| self.class.contains(symbol.ch))
+            .map(|(symbol, _)| schematic.numbers_of(symbol))
+            .filter(|numbers| self.count.matches(numbers.len()))
+            .filter_map(|numbers| {
+                let values: Vec<u64> = numbers
+                    .iter()
+                    .map(|number| schematic.numbers[*number].value)
+                    .collect();
+                self.reducer.reduce(&values)
+            })
+            .sum()
+    }
+}
+
+/// Symbols whose number of adjacent numbers doesn't match `expected`, with that number.
+pub fn unusual_symbols(schematic: &Schematic, expected: Count) -> Vec<(usize, usize)> {
+    (0..schematic.symbols.len())
+        .map(|symbol| (symbol, schematic.numbers_of(symbol).len()))
+        .filter(|(_, count)| !expected.matches(*count))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rules() {
+        let schematic = Schematic::parse(&crate::schematic::example());
+
+        assert_eq!(
+            Rule::parse("*:2:product").unwrap().apply(&schematic),
+            467 * 35 + 2 * 755 * 598
+        );
+        assert_eq!(
+            Rule::parse("*+:>=1:sum").unwrap().apply(&schematic),
+            467 + 35 + 617 + 592 + 2 * (755 + 598)
+        );
+        assert_eq!(
+            Rule::parse("any:any:max").unwrap().apply(&schematic),
+            467 + 633 + 617 + 592 + 664 + 755 + 755
+        );
+        assert_eq!(
+            Rule::parse("::1:count").unwrap().class,
+            SymbolClass::OneOf(vec![':'])
+        );
+        assert!(Rule::parse("*:two:product").is_err());
+        assert!(Rule::parse("*:2").is_err());
+
+        let unusual = unusual_symbols(&schematic, Count::parse("2").unwrap());
+        let chars: Vec<char> = unusual
+            .iter()
+            .map(|(symbol, _)| schematic.symbols[*symbol].ch)
+            .collect();
+        assert_eq!(chars, vec!['#', '*', '+', '$']);
+        assert!(unusual.iter().all(|(_, count)| *count == 1));
+    }
+}
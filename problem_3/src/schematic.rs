@@ -0,0 +1,154 @@
+use std::{collections::HashMap, ops::Range};
+
+pub type Pos = (usize, usize);
+
+pub fn is_symbol(ch: &char) -> bool {
+    !ch.is_ascii_digit() && *ch != '.'
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartNumber {
+    pub value: u64,
+    pub row: usize,
+    pub col_span: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub ch: char,
+    pub pos: Pos,
+}
+
+/// Every number and symbol of the engine schematic, with the adjacency between them indexed both
+/// ways. Numbers and symbols are referred to by their index in `numbers` and `symbols`.
+#[derive(Debug, Default)]
+pub struct Schematic {
+    pub numbers: Vec<PartNumber>,
+    pub symbols: Vec<Symbol>,
+    number_symbols: Vec<Vec<usize>>,
+    symbol_numbers: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    pub fn parse(lines: &[String]) -> Schematic {
+        let mut schematic = Schematic::default();
+        let mut symbol_at: HashMap<Pos, usize> = HashMap::new();
+
+        for (row, line) in lines.iter().enumerate() {
+            let mut start: Option<usize> = None;
+            for (col, ch) in line.chars().chain(['.']).enumerate() {
+                if ch.is_ascii_digit() {
+                    start.get_or_insert(col);
+                    continue;
+                }
+                if let Some(start) = start.take() {
+                    let digits: String = line.chars().skip(start).take(col - start).collect();
+                    schematic.numbers.push(PartNumber {
+                        value: digits.parse().unwrap(),
+                        row,
+                        col_span: start..col,
+                    });
+                }
+                if is_symbol(&ch) {
+                    symbol_at.insert((row, col), schematic.symbols.len());
+                    schematic.symbols.push(Symbol {
+                        ch,
+                        pos: (row, col),
+                    });
+                }
+            }
+        }
+
+        schematic.symbol_numbers = vec![vec![]; schematic.symbols.len()];
+        for (number_index, number) in schematic.numbers.iter().enumerate() {
+            let mut symbols: Vec<usize> = vec![];
+            for row in number.row.saturating_sub(1)..=(number.row + 1) {
+                for col in number.col_span.start.saturating_sub(1)..=number.col_span.end {
+                    if let Some(symbol) = symbol_at.get(&(row, col)) {
+                        symbols.push(*symbol);
+                        schematic.symbol_numbers[*symbol].push(number_index);
+                    }
+                }
+            }
+            schematic.number_symbols.push(symbols);
+        }
+
+        schematic
+    }
+
+    /// Symbols adjacent to the number.
+    pub fn symbols_of(&self, number: usize) -> &[usize] {
+        &self.number_symbols[number]
+    }
+
+    /// Numbers adjacent to the symbol.
+    pub fn numbers_of(&self, symbol: usize) -> &[usize] {
+        &self.symbol_numbers[symbol]
+    }
+
+    /// Numbers next to at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .enumerate()
+            .filter(|(number, _)| !self.symbols_of(*number).is_empty())
+            .map(|(_, number)| number)
+    }
+
+    /// Numbers next to at least one `ch` symbol.
+    pub fn numbers_adjacent_to(&self, ch: char) -> Vec<&PartNumber> {
+        self.numbers
+            .iter()
+            .enumerate()
+            .filter(|(number, _)| {
+                self.symbols_of(*number)
+                    .iter()
+                    .any(|symbol| self.symbols[*symbol].ch == ch)
+            })
+            .map(|(_, number)| number)
+            .collect()
+    }
+}
+
+/// The puzzle example from `test.txt`, shared by the tests of every module.
+#[cfg(test)]
+pub fn example() -> Vec<String> {
+    std::fs::read_to_string("test.txt")
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn schematic() -> Schematic {
+        Schematic::parse(&example())
+    }
+
+    #[test]
+    fn test_index() {
+        let schematic = schematic();
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(schematic.symbols.len(), 7);
+        assert_eq!(
+            schematic.numbers[0],
+            PartNumber {
+                value: 467,
+                row: 0,
+                col_span: 0..3
+            }
+        );
+        assert_eq!(schematic.part_numbers().map(|n| n.value).sum::<u64>(), 4361);
+
+        let hash: Vec<u64> = schematic
+            .numbers_adjacent_to('#')
+            .iter()
+            .map(|number| number.value)
+            .collect();
+        assert_eq!(hash, vec![633]);
+        assert_eq!(schematic.numbers_of(0), &[0, 2]);
+    }
+}
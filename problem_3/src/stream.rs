@@ -0,0 +1,141 @@
+use std::io::{self, BufRead};
+
+use crate::schematic::is_symbol;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub part1: u64,
+    pub part2: u64,
+}
+
+/// Cell of a row, anything past its end or outside the schematic reading as '.'.
+fn cell(row: Option<&Vec<char>>, col: usize) -> char {
+    row.and_then(|row| row.get(col)).copied().unwrap_or('.')
+}
+
+/// Numbers of the row with the columns they span, end excluded.
+fn numbers(row: &[char]) -> Vec<(u64, usize, usize)> {
+    let mut numbers: Vec<(u64, usize, usize)> = vec![];
+    let mut start: Option<usize> = None;
+    for (col, ch) in row.iter().chain(['.'].iter()).enumerate() {
+        match (ch.is_ascii_digit(), start) {
+            (true, None) => start = Some(col),
+            (false, Some(number_start)) => {
+                let digits: String = row[number_start..col].iter().collect();
+                numbers.push((digits.parse().unwrap(), number_start, col));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    numbers
+}
+
+/// Both parts for the middle row of the window, `above` and `below` being `None` at the edges.
+fn process_row(above: Option<&Vec<char>>, row: &Vec<char>, below: Option<&Vec<char>>) -> Totals {
+    let window = [above, Some(row), below];
+    let mut totals = Totals::default();
+
+    for (value, start, end) in numbers(row) {
+        let next_to_symbol = window.iter().any(|neighbour_row| {
+            (start.saturating_sub(1)..=end).any(|col| is_symbol(&cell(*neighbour_row, col)))
+        });
+        if next_to_symbol {
+            totals.part1 += value;
+        }
+    }
+
+    let window_numbers: Vec<(u64, usize, usize)> = window
+        .iter()
+        .flatten()
+        .flat_map(|neighbour_row| numbers(neighbour_row))
+        .collect();
+    for (col, _) in row.iter().enumerate().filter(|(_, ch)| **ch == '*') {
+        let adjacent: Vec<u64> = window_numbers
+            .iter()
+            .filter(|(_, start, end)| start.saturating_sub(1) <= col && col <= *end)
+            .map(|(value, _, _)| *value)
+            .collect();
+        if adjacent.len() == 2 {
+            totals.part2 += adjacent[0] * adjacent[1];
+        }
+    }
+
+    totals
+}
+
+/// Solves both parts in one pass while only holding three rows, so the schematic can be as tall
+/// as needed. Rows may have different lengths.
+pub fn solve(lines: impl Iterator<Item = io::Result<String>>) -> io::Result<Totals> {
+    let mut totals = Totals::default();
+    let mut above: Option<Vec<char>> = None;
+    let mut current: Option<Vec<char>> = None;
+
+    for line in lines {
+        let below: Vec<char> = line?.chars().collect();
+        if let Some(row) = current.as_ref() {
+            let row_totals = process_row(above.as_ref(), row, Some(&below));
+            totals.part1 += row_totals.part1;
+            totals.part2 += row_totals.part2;
+        }
+        above = current;
+        current = Some(below);
+    }
+    if let Some(row) = current.as_ref() {
+        let row_totals = process_row(above.as_ref(), row, None);
+        totals.part1 += row_totals.part1;
+        totals.part2 += row_totals.part2;
+    }
+
+    Ok(totals)
+}
+
+pub fn solve_reader(reader: impl BufRead) -> io::Result<Totals> {
+    solve(reader.lines())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+    use crate::schematic::Schematic;
+
+    fn check<S: AsRef<str>>(lines: &[S]) {
+        let owned: Vec<String> = lines.iter().map(|line| line.as_ref().to_string()).collect();
+        let schematic = Schematic::parse(&owned);
+        let expected = Totals {
+            part1: schematic.part_numbers().map(|number| number.value).sum(),
+            part2: Rule::parse("*:2:product").unwrap().apply(&schematic),
+        };
+        let text = owned.join("\n");
+        assert_eq!(solve_reader(text.as_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_matches_schematic() {
+        check(&crate::schematic::example());
+        check(&["12*34"]);
+        check::<&str>(&[]);
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        check(&[
+            "467..114",
+            "...*",
+            "..35..633.#",
+            "",
+            "1",
+            "*2",
+            "..........7",
+            "...........*",
+        ]);
+        assert_eq!(
+            solve_reader("5\n.*\n3".as_bytes()).unwrap(),
+            Totals {
+                part1: 8,
+                part2: 15
+            }
+        );
+    }
+}
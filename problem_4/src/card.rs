@@ -0,0 +1,218 @@
+use std::{collections::HashSet, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub id: usize,
+    pub winning: Vec<u64>,
+    pub have: Vec<u64>,
+}
+
+/// What to do when a number shows up twice in the winning list or in the list we have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Duplicates {
+    /// Winning duplicates count once, duplicates we have count each time.
+    Merge,
+    /// Same as `Merge`, printing a warning on stderr.
+    Warn,
+    Reject,
+}
+
+impl Duplicates {
+    pub fn from_arg(arg: &str) -> Option<Duplicates> {
+        match arg {
+            "merge" => Some(Duplicates::Merge),
+            "warn" => Some(Duplicates::Warn),
+            "reject" => Some(Duplicates::Reject),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardError {
+    Parse {
+        line: usize,
+        message: String,
+    },
+    NotSequential {
+        expected: usize,
+        found: usize,
+    },
+    Duplicate {
+        id: usize,
+        list: &'static str,
+        number: u64,
+    },
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            CardError::NotSequential { expected, found } => {
+                write!(f, "expected card {}, found card {}", expected, found)
+            }
+            CardError::Duplicate { id, list, number } => {
+                write!(
+                    f,
+                    "card {}: {} appears twice in the {} numbers",
+                    id, number, list
+                )
+            }
+        }
+    }
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<u64>, String> {
+    s.split_whitespace()
+        .map(|item| {
+            item.parse::<u64>()
+                .map_err(|_| format!("'{}' is not a number", item))
+        })
+        .collect()
+}
+
+impl Card {
+    /// Parses `Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53`.
+    pub fn parse(line: &str) -> Result<Card, String> {
+        let (header, numbers) = line
+            .split_once(':')
+            .ok_or_else(|| format!("missing ':' in '{}'", line))?;
+        let id = header
+            .trim()
+            .strip_prefix("Card")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or_else(|| format!("expected 'Card <id>', got '{}'", header))?;
+        let (winning, have) = numbers
+            .split_once('|')
+            .ok_or_else(|| format!("missing '|' in '{}'", line))?;
+
+        Ok(Card {
+            id,
+            winning: parse_numbers(winning)?,
+            have: parse_numbers(have)?,
+        })
+    }
+
+    /// Numbers listed more than once, with the list they are in.
+    pub fn duplicates(&self) -> Vec<(&'static str, u64)> {
+        let mut duplicates: Vec<(&'static str, u64)> = vec![];
+        for (list, numbers) in [("winning", &self.winning), ("own", &self.have)] {
+            let mut seen: HashSet<u64> = HashSet::new();
+            for number in numbers.iter() {
+                if !seen.insert(*number) && !duplicates.contains(&(list, *number)) {
+                    duplicates.push((list, *number));
+                }
+            }
+        }
+        duplicates
+    }
+
+    /// How many of our numbers are winning ones.
+    pub fn match_count(&self) -> usize {
+        let winning: HashSet<&u64> = self.winning.iter().collect();
+        self.have
+            .iter()
+            .filter(|number| winning.contains(number))
+            .count()
+    }
+}
+
+/// Parses every card, checking they are numbered 1, 2, 3, ... in order. With `Duplicates::Warn`
+/// the duplicates found come back along with the cards, for the caller to report.
+pub fn parse_cards(
+    lines: &[String],
+    duplicates: Duplicates,
+) -> Result<(Vec<Card>, Vec<CardError>), CardError> {
+    let mut cards: Vec<Card> = vec![];
+    let mut warnings: Vec<CardError> = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        let card = Card::parse(line).map_err(|message| CardError::Parse {
+            line: index + 1,
+            message,
+        })?;
+        if card.id != index + 1 {
+            return Err(CardError::NotSequential {
+                expected: index + 1,
+                found: card.id,
+            });
+        }
+        for (list, number) in card.duplicates() {
+            let error = CardError::Duplicate {
+                id: card.id,
+                list,
+                number,
+            };
+            match duplicates {
+                Duplicates::Merge => {}
+                Duplicates::Warn => warnings.push(error),
+                Duplicates::Reject => return Err(error),
+            }
+        }
+        cards.push(card);
+    }
+    Ok((cards, warnings))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let card = Card::parse("Card   1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").unwrap();
+        assert_eq!(card.id, 1);
+        assert_eq!(card.winning, vec![41, 48, 83, 86, 17]);
+        assert_eq!(card.match_count(), 4);
+        assert!(card.duplicates().is_empty());
+        assert!(Card::parse("Card x: 1 | 2").is_err());
+        assert!(Card::parse("Card 1: 1 2").is_err());
+    }
+
+    #[test]
+    fn test_sequence_and_duplicates() {
+        let cards = lines(&["Card 1: 1 2 | 3", "Card 3: 1 | 1"]);
+        assert_eq!(
+            parse_cards(&cards, Duplicates::Merge),
+            Err(CardError::NotSequential {
+                expected: 2,
+                found: 3
+            })
+        );
+
+        let cards = lines(&["Card 1: 5 5 6 | 5 7 7 7"]);
+        let (parsed, warnings) = parse_cards(&cards, Duplicates::Merge).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(parsed[0].match_count(), 1);
+        assert_eq!(parsed[0].duplicates(), vec![("winning", 5), ("own", 7)]);
+        let (warned, warnings) = parse_cards(&cards, Duplicates::Warn).unwrap();
+        assert_eq!(warned, parsed);
+        assert_eq!(
+            warnings,
+            vec![
+                CardError::Duplicate {
+                    id: 1,
+                    list: "winning",
+                    number: 5
+                },
+                CardError::Duplicate {
+                    id: 1,
+                    list: "own",
+                    number: 7
+                }
+            ]
+        );
+        assert_eq!(
+            parse_cards(&cards, Duplicates::Reject),
+            Err(CardError::Duplicate {
+                id: 1,
+                list: "winning",
+                number: 5
+            })
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::{fmt, iter::Sum};
+
+/// What happens to copies won past the last card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// They are lost.
+    Clamp,
+    /// They go to the first cards again. Those were already scored, so they don't win anything.
+    Wrap,
+    Error,
+}
+
+impl Overflow {
+    pub fn from_arg(arg: &str) -> Option<Overflow> {
+        match arg {
+            "clamp" => Some(Overflow::Clamp),
+            "wrap" => Some(Overflow::Wrap),
+            "error" => Some(Overflow::Error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowError {
+    /// With `Overflow::Error`, a card wins copies past the last card.
+    PastLastCard {
+        card: usize,
+        matches: usize,
+        cards_left: usize,
+    },
+    /// The copies held of a card no longer fit in the count type.
+    TooManyCopies { card: usize },
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverflowError::PastLastCard {
+                card,
+                matches,
+                cards_left,
+            } => write!(
+                f,
+                "card {} wins copies of the next {} cards, but only {} are left",
+                card, matches, cards_left
+            ),
+            OverflowError::TooManyCopies { card } => {
+                write!(f, "too many copies of card {} to count", card)
+            }
+        }
+    }
+}
+
+/// Numbers copies can be counted in.
+pub trait Count: Copy + From<u32> + Sum<Self> {
+    /// `None` when the result can't be represented.
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+impl Count for u64 {
+    fn checked_add(self, other: u64) -> Option<u64> {
+        u64::checked_add(self, other)
+    }
+
+    fn checked_mul(self, other: u64) -> Option<u64> {
+        u64::checked_mul(self, other)
+    }
+}
+
+/// Goes to infinity rather than failing.
+impl Count for f64 {
+    fn checked_add(self, other: f64) -> Option<f64> {
+        Some(self + other)
+    }
+
+    fn checked_mul(self, other: f64) -> Option<f64> {
+        Some(self * other)
+    }
+}
+
+/// Copies are counted in `T`, which is `f64` for simulated piles that would overflow a `u64`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cascade<T = u64> {
+    /// Copies held of each card, originals included.
+    pub copies: Vec<T>,
+    /// Copies of other cards won by each card.
+    pub generated: Vec<T>,
+}
+
+impl<T: Count> Cascade<T> {
+    /// Can't overflow, `cascade` checks the total fits.
+    pub fn total(&self) -> T {
+        self.copies.iter().copied().sum()
+    }
+}
+
+/// Plays the cards in order, each copy of a card with `n` matches winning a copy of the next `n`
+/// cards. `match_counts` holds the matches of each card. Fails with `TooManyCopies` rather than
+/// overflowing `T`, which can happen with `Overflow::Wrap` where copies keep feeding back.
+pub fn cascade<T: Count>(
+    match_counts: &[usize],
+    overflow: Overflow,
+) -> Result<Cascade<T>, OverflowError> {
+    let len = match_counts.len();
+    let mut copies: Vec<T> = vec![T::from(1); len];
+    let mut generated: Vec<T> = vec![T::from(0); len];
+
+    for (card, matches) in match_counts.iter().enumerate() {
+        let cards_left = len - card - 1;
+        let targets = match (overflow, *matches > cards_left) {
+            (_, false) | (Overflow::Wrap, true) => *matches,
+            (Overflow::Clamp, true) => cards_left,
+            (Overflow::Error, true) => {
+                return Err(OverflowError::PastLastCard {
+                    card: card + 1,
+                    matches: *matches,
+                    cards_left,
+                })
+            }
+        };
+        let held = copies[card];
+        for offset in 1..=targets {
+            let target = (card + offset) % len;
+            copies[target] = copies[target]
+                .checked_add(held)
+                .ok_or(OverflowError::TooManyCopies { card: target + 1 })?;
+        }
+        generated[card] = held
+            .checked_mul(T::from(targets as u32))
+            .ok_or(OverflowError::TooManyCopies { card: card + 1 })?;
+    }
+
+    // The total can overflow even when every card fits
+    copies
+        .iter()
+        .enumerate()
+        .try_fold(T::from(0), |sum, (card, held)| {
+            sum.checked_add(*held)
+                .ok_or(OverflowError::TooManyCopies { card: card + 1 })
+        })?;
+    Ok(Cascade { copies, generated })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cascade() {
+        let cascade_result: Cascade = cascade(&[4, 2, 2, 1, 0, 0], Overflow::Error).unwrap();
+        assert_eq!(cascade_result.copies, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(cascade_result.generated, vec![4, 4, 8, 8, 0, 0]);
+        assert_eq!(cascade_result.total(), 30);
+        assert_eq!(
+            cascade_result.total(),
+            6 + cascade_result.generated.iter().sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let matches = [1, 0, 3];
+        assert_eq!(
+            cascade::<u64>(&matches, Overflow::Error),
+            Err(OverflowError::PastLastCard {
+                card: 3,
+                matches: 3,
+                cards_left: 0
+            })
+        );
+        assert_eq!(
+            cascade::<u64>(&matches, Overflow::Clamp).unwrap().copies,
+            vec![1, 2, 1]
+        );
+
+        let wrapped: Cascade = cascade(&matches, Overflow::Wrap).unwrap();
+        assert_eq!(wrapped.copies, vec![2, 3, 2]);
+        assert_eq!(wrapped.generated, vec![1, 0, 3]);
+
+        // Wrapping around, each card wins a copy of every card, so the copies about double per card
+        let doubling = [100; 100];
+        assert_eq!(
+            cascade::<u64>(&doubling, Overflow::Wrap),
+            Err(OverflowError::TooManyCopies { card: 59 })
+        );
+        assert!(cascade::<f64>(&doubling, Overflow::Wrap).is_ok());
+    }
+}
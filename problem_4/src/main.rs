@@ -1,108 +1,183 @@
+mod card;
+mod cascade;
+mod explain;
+mod scoring;
+mod simulate;
+
 use std::{
-    collections::HashSet,
+    env,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader},
+    process,
 };
 
-fn part1(line: &String) -> u64 {
-    let numbers = line
-        .split(":")
-        .collect::<Vec<&str>>()
-        .get(1)
-        .unwrap()
-        .split("|")
-        .collect::<Vec<&str>>();
-
-    let winning_numbers: HashSet<u64> = numbers
-        .get(0)
-        .unwrap()
-        .split(" ")
-        .map(|item| item.trim())
-        .filter(|item| !item.is_empty())
-        .map(|item| item.parse::<u64>().unwrap())
-        .collect();
-
-    let mut guessed_number_count: u32 = 0;
-
-    numbers
-        .get(1)
-        .unwrap()
-        .split(" ")
-        .map(|item| item.trim())
-        .filter(|item| !item.is_empty())
-        .map(|item| item.parse::<u64>().unwrap())
-        .for_each(|number| {
-            if winning_numbers.contains(&number) {
-                guessed_number_count += 1;
-            }
-        });
-
-    if guessed_number_count == 0 {
-        return 0;
-    }
+use card::{Card, Duplicates};
+use cascade::{Cascade, Overflow};
+use explain::Trace;
+use scoring::Scoring;
 
-    (2 as u64).pow(guessed_number_count - 1)
+fn part1(cards: &[Card], scoring: &Scoring) -> Result<u64, String> {
+    cards.iter().try_fold(0_u64, |sum, card| {
+        sum.checked_add(scoring.score(card.match_count())?)
+            .ok_or_else(|| "total score overflows".to_string())
+    })
 }
 
-fn part2(lines: Vec<String>) {
-    let mut game_counts: Vec<u64> = vec![1; lines.len()];
-
-    for (game_id, game) in lines.iter().enumerate() {
-        let numbers = game
-            .split(":")
-            .collect::<Vec<&str>>()
-            .get(1)
-            .unwrap()
-            .split("|")
-            .collect::<Vec<&str>>();
-
-        let winning_numbers: HashSet<u64> = numbers
-            .get(0)
-            .unwrap()
-            .split(" ")
-            .map(|item| item.trim())
-            .filter(|item| !item.is_empty())
-            .map(|item| item.parse::<u64>().unwrap())
-            .collect();
-
-        let mut guessed_number_count: usize = 0;
-
-        numbers
-            .get(1)
-            .unwrap()
-            .split(" ")
-            .map(|item| item.trim())
-            .filter(|item| !item.is_empty())
-            .map(|item| item.parse::<u64>().unwrap())
-            .for_each(|number| {
-                if winning_numbers.contains(&number) {
-                    guessed_number_count += 1;
-                }
-            });
+fn part2(cards: &[Card], overflow: Overflow, show_copies: bool, trace: &mut Trace) {
+    let match_counts: Vec<usize> = cards.iter().map(|card| card.match_count()).collect();
+    let result: Cascade = cascade::cascade(&match_counts, overflow).unwrap_or_else(|e| {
+        println!("Part 2: {}", e);
+        process::exit(1);
+    });
+
+    for (index, card) in cards.iter().enumerate() {
+        trace.record(
+            card.id,
+            "card",
+            vec![
+                ("matches", match_counts[index].into()),
+                ("copies", result.copies[index].into()),
+                ("generated", result.generated[index].into()),
+            ],
+        );
+    }
 
-        for i in (game_id + 1)..(game_id + 1 + guessed_number_count) {
-            game_counts[i] += game_counts[game_id];
+    if show_copies {
+        for (index, card) in cards.iter().enumerate() {
+            println!(
+                "Card {}: {} copies, won {} copies of other cards",
+                card.id, result.copies[index], result.generated[index]
+            );
         }
     }
 
-    println!("{:?}", game_counts);
+    println!("Part 2: {}", result.total());
+}
+
+/// Returns the value following `name` in the arguments, if there is one.
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == name)?;
+    match args.get(position + 1) {
+        Some(value) => Some(value),
+        None => usage(),
+    }
+}
 
-    println!("Part 2: {}", game_counts.iter().sum::<u64>());
+fn usage() -> ! {
+    println!(
+        "Usage: problem_4 [--duplicates merge|warn|reject] [--scoring doubling|linear|<formula in n>]"
+    );
+    println!("                 [--overflow clamp|wrap|error] [--copies] [--explain[=jsonl]]");
+    println!(
+        "       problem_4 simulate [--pool size] [--runs count] [--seed seed] [--overflow ...]"
+    );
+    println!("       problem_4 sensitivity [--overflow ...]");
+    process::exit(1);
 }
 
 fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    let mut trace = Trace::new(explain::take_flag(&mut args));
+    let duplicates = match option(&args, "--duplicates") {
+        Some(arg) => Duplicates::from_arg(arg).unwrap_or_else(|| usage()),
+        None => Duplicates::Warn,
+    };
+    let scoring =
+        Scoring::parse(option(&args, "--scoring").unwrap_or("doubling")).unwrap_or_else(|e| {
+            println!("Invalid scoring: {}", e);
+            usage();
+        });
+    let overflow = match option(&args, "--overflow") {
+        Some(arg) => Overflow::from_arg(arg).unwrap_or_else(|| usage()),
+        None => Overflow::Error,
+    };
+    let show_copies = args.iter().any(|arg| arg == "--copies");
+
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
 
-    let mut result_part1 = 0;
-    let mut lines: Vec<String> = vec![];
+    let lines: Vec<String> = reader
+        .lines()
+        .map(|line_result| line_result.unwrap())
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let (cards, warnings) = card::parse_cards(&lines, duplicates).unwrap_or_else(|e| {
+        println!("Invalid cards: {}", e);
+        process::exit(1);
+    });
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("simulate") => {
+            let number = |name: &str, default: u64| match option(&args, name) {
+                Some(value) => value.parse::<u64>().unwrap_or_else(|_| usage()),
+                None => default,
+            };
+            // Random piles easily win past the last card, so clamp unless told otherwise
+            let overflow = match option(&args, "--overflow") {
+                Some(_) => overflow,
+                None => Overflow::Clamp,
+            };
+            let pool = number("--pool", 99);
+            let longest = cards
+                .iter()
+                .map(|card| card.winning.len().max(card.have.len()))
+                .max()
+                .unwrap_or(0);
+            if pool < longest as u64 || pool == 0 {
+                println!("The pool needs at least {} numbers", longest.max(1));
+                process::exit(1);
+            }
+            let runs = number("--runs", 1000) as usize;
+            if runs == 0 {
+                println!("Need at least one run");
+                process::exit(1);
+            }
+            match simulate::random_piles(&cards, pool, runs, number("--seed", 2023), overflow) {
+                Ok(distribution) => print!("{}", distribution.report()),
+                Err(e) => println!("{}", e),
+            }
+            return;
+        }
+        // Flipping a number can win past the last card as well
+        Some("sensitivity") => {
+            let overflow = match option(&args, "--overflow") {
+                Some(_) => overflow,
+                None => Overflow::Clamp,
+            };
+            match simulate::flip_sensitivity(&cards, overflow) {
+                Ok(mut flips) => {
+                    print!("{}", simulate::flip_distribution(&flips).report());
+                    flips.sort_by_key(|flip| -flip.delta.abs());
+                    for flip in flips.iter().take(10) {
+                        println!(
+                            "Card {}: {} one of {} numbers changes part 2 by {}",
+                            flip.card,
+                            match flip.gained_match {
+                                true => "winning",
+                                false => "losing",
+                            },
+                            flip.numbers,
+                            flip.delta
+                        );
+                    }
+                }
+                Err(e) => println!("{}", e),
+            }
+            return;
+        }
+        Some(arg) if !arg.starts_with("--") => usage(),
+        _ => {}
+    }
 
-    for line_result in reader.lines() {
-        lines.push(line_result.as_ref().unwrap().clone());
-        result_part1 += part1(&line_result.unwrap());
+    match part1(&cards, &scoring) {
+        Ok(result_part1) => println!("Part 1: {}", result_part1),
+        Err(e) => println!("Part 1: {}", e),
     }
 
-    println!("Part 1: {}", result_part1);
+    part2(&cards, overflow, show_copies, &mut trace);
 
-    part2(lines);
+    trace.write(&mut io::stderr().lock()).unwrap();
 }
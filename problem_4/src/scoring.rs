@@ -0,0 +1,191 @@
+use std::{iter::Peekable, str::Chars};
+
+/// Points for a card with `n` matching numbers, cards without matches scoring nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scoring {
+    /// 1, 2, 4, 8, ...
+    Doubling,
+    /// 1, 2, 3, 4, ...
+    Linear,
+    Formula(Expr),
+}
+
+impl Scoring {
+    /// `doubling`, `linear`, or a formula in `n` like `n*(n+1)/2` or `3^(n-1)`.
+    pub fn parse(s: &str) -> Result<Scoring, String> {
+        match s {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            _ => Ok(Scoring::Formula(Expr::parse(s)?)),
+        }
+    }
+
+    pub fn score(&self, n: usize) -> Result<u64, String> {
+        if n == 0 {
+            return Ok(0);
+        }
+        match self {
+            Scoring::Doubling => u32::try_from(n - 1)
+                .ok()
+                .and_then(|exponent| 2_u64.checked_pow(exponent))
+                .ok_or_else(|| "score overflows".to_string()),
+            Scoring::Linear => Ok(n as u64),
+            Scoring::Formula(expr) => {
+                let score = expr.eval(n as i64)?;
+                u64::try_from(score).map_err(|_| format!("negative score {} for n = {}", score, n))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    N,
+    Number(i64),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn parse(s: &str) -> Result<Expr, String> {
+        let mut chars = s.chars().peekable();
+        let expr = parse_sum(&mut chars)?;
+        skip_spaces(&mut chars);
+        match chars.next() {
+            None => Ok(expr),
+            Some(c) => Err(format!("unexpected '{}' in '{}'", c, s)),
+        }
+    }
+
+    pub fn eval(&self, n: i64) -> Result<i64, String> {
+        let overflow = || "score overflows".to_string();
+        match self {
+            Expr::N => Ok(n),
+            Expr::Number(value) => Ok(*value),
+            Expr::Neg(expr) => expr.eval(n)?.checked_neg().ok_or_else(overflow),
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (left.eval(n)?, right.eval(n)?);
+                match op {
+                    '+' => left.checked_add(right).ok_or_else(overflow),
+                    '-' => left.checked_sub(right).ok_or_else(overflow),
+                    '*' => left.checked_mul(right).ok_or_else(overflow),
+                    '/' => left
+                        .checked_div(right)
+                        .ok_or_else(|| "division by zero".to_string()),
+                    '^' => u32::try_from(right)
+                        .map_err(|_| format!("bad exponent {}", right))
+                        .and_then(|right| left.checked_pow(right).ok_or_else(overflow)),
+                    _ => unreachable!("What is this '{}' operator?", op),
+                }
+            }
+        }
+    }
+}
+
+fn skip_spaces(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn parse_sum(chars: &mut Peekable<Chars>) -> Result<Expr, String> {
+    let mut expr = parse_product(chars)?;
+    loop {
+        skip_spaces(chars);
+        match chars.peek() {
+            Some(&op) if op == '+' || op == '-' => {
+                chars.next();
+                expr = Expr::Binary(op, Box::new(expr), Box::new(parse_product(chars)?));
+            }
+            _ => return Ok(expr),
+        }
+    }
+}
+
+fn parse_product(chars: &mut Peekable<Chars>) -> Result<Expr, String> {
+    let mut expr = parse_power(chars)?;
+    loop {
+        skip_spaces(chars);
+        match chars.peek() {
+            Some(&op) if op == '*' || op == '/' => {
+                chars.next();
+                expr = Expr::Binary(op, Box::new(expr), Box::new(parse_power(chars)?));
+            }
+            _ => return Ok(expr),
+        }
+    }
+}
+
+/// `^` is right associative and binds tighter than unary minus on its left.
+fn parse_power(chars: &mut Peekable<Chars>) -> Result<Expr, String> {
+    skip_spaces(chars);
+    if chars.peek() == Some(&'-') {
+        chars.next();
+        return Ok(Expr::Neg(Box::new(parse_power(chars)?)));
+    }
+    let base = parse_atom(chars)?;
+    skip_spaces(chars);
+    if chars.peek() == Some(&'^') {
+        chars.next();
+        return Ok(Expr::Binary(
+            '^',
+            Box::new(base),
+            Box::new(parse_power(chars)?),
+        ));
+    }
+    Ok(base)
+}
+
+fn parse_atom(chars: &mut Peekable<Chars>) -> Result<Expr, String> {
+    skip_spaces(chars);
+    match chars.next() {
+        Some('n') => Ok(Expr::N),
+        Some('(') => {
+            let expr = parse_sum(chars)?;
+            skip_spaces(chars);
+            match chars.next() {
+                Some(')') => Ok(expr),
+                _ => Err("missing ')'".to_string()),
+            }
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let mut digits = String::from(c);
+            while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(*c);
+                chars.next();
+            }
+            digits
+                .parse()
+                .map(Expr::Number)
+                .map_err(|_| format!("'{}' is too big", digits))
+        }
+        Some(c) => Err(format!("unexpected '{}'", c)),
+        None => Err("unexpected end of formula".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scoring() {
+        let doubling = Scoring::parse("doubling").unwrap();
+        let formula = Scoring::parse("2^(n-1)").unwrap();
+        for n in 0..10 {
+            assert_eq!(doubling.score(n), formula.score(n));
+        }
+        assert_eq!(Scoring::parse("linear").unwrap().score(4), Ok(4));
+        assert_eq!(Scoring::parse("n * (n + 1) / 2").unwrap().score(4), Ok(10));
+        assert_eq!(Scoring::parse("2^3^2").unwrap().score(1), Ok(512));
+        assert_eq!(Scoring::parse("-n^2 + 20").unwrap().score(4), Ok(4));
+        assert_eq!(doubling.score(64), Ok(1 << 63));
+        assert_eq!(doubling.score(65), formula.score(65));
+        assert!(doubling.score(65).is_err());
+        assert!(Scoring::parse("n - 5").unwrap().score(1).is_err());
+        assert!(Scoring::parse("n / (n - 1)").unwrap().score(1).is_err());
+        assert!(Scoring::parse("n +").is_err());
+        assert!(Scoring::parse("(n").is_err());
+        assert!(Scoring::parse("x").is_err());
+    }
+}
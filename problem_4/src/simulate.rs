@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+
+use crate::card::Card;
+use crate::cascade::{self, Overflow, OverflowError};
+
+/// SplitMix64, small and good enough for Monte Carlo runs that need to be reproducible.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`, without modulo bias.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+
+    /// `count` distinct numbers out of `1..=pool`.
+    pub fn distinct(&mut self, pool: u64, count: usize) -> Vec<u64> {
+        // Redrawing the odd repeat beats laying out a huge pool when only a few numbers are needed
+        if (count as u64).saturating_mul(4) <= pool {
+            let mut seen: HashSet<u64> = HashSet::with_capacity(count);
+            let mut numbers: Vec<u64> = Vec::with_capacity(count);
+            while numbers.len() < count {
+                let number = 1 + self.below(pool);
+                if seen.insert(number) {
+                    numbers.push(number);
+                }
+            }
+            return numbers;
+        }
+
+        let mut numbers: Vec<u64> = (1..=pool).collect();
+        for i in 0..count {
+            let j = i + self.below((numbers.len() - i) as u64) as usize;
+            numbers.swap(i, j);
+        }
+        numbers.truncate(count);
+        numbers
+    }
+}
+
+/// Summary of a set of sampled values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    samples: Vec<f64>,
+}
+
+/// Plain for everyday numbers, scientific once they get silly.
+fn format_value(value: f64) -> String {
+    match value.abs() < 1e12 {
+        true => format!("{:.2}", value),
+        false => format!("{:.4e}", value),
+    }
+}
+
+impl Distribution {
+    pub fn new(mut samples: Vec<f64>) -> Distribution {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        Distribution { samples }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// `None` for every statistic of an empty distribution.
+    pub fn mean(&self) -> Option<f64> {
+        match self.samples.is_empty() {
+            true => None,
+            false => Some(self.samples.iter().sum::<f64>() / self.len() as f64),
+        }
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let variance =
+            self.samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / self.len() as f64;
+        Some(variance.sqrt())
+    }
+
+    /// Nearest-rank percentile, `p` going from 0 to 100.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        let rank = ((p / 100.0) * self.len() as f64).ceil() as usize;
+        self.samples
+            .get(rank.clamp(1, self.len().max(1)) - 1)
+            .copied()
+    }
+
+    /// Sample counts over `buckets` equal-width buckets between the smallest and largest sample,
+    /// with the lower bound of each bucket. Empty without samples.
+    pub fn histogram(&self, buckets: usize) -> Vec<(f64, usize)> {
+        let (Some(min), Some(max)) = (self.samples.first().copied(), self.samples.last().copied())
+        else {
+            return vec![];
+        };
+        let width = (max - min) / buckets as f64;
+        let mut counts: Vec<(f64, usize)> = (0..buckets)
+            .map(|bucket| (min + bucket as f64 * width, 0))
+            .collect();
+        for sample in self.samples.iter() {
+            let bucket = match width > 0.0 {
+                true => (((sample - min) / width) as usize).min(buckets - 1),
+                false => 0,
+            };
+            counts[bucket].1 += 1;
+        }
+        counts
+    }
+
+    pub fn report(&self) -> String {
+        let (Some(mean), Some(std_dev)) = (self.mean(), self.std_dev()) else {
+            return "samples 0\n".to_string();
+        };
+        let percentile = |p: f64| format_value(self.percentile(p).unwrap());
+        let mut report = format!(
+            "samples {}, mean {}, std dev {}\nmin {}, p5 {}, median {}, p95 {}, max {}\n",
+            self.len(),
+            format_value(mean),
+            format_value(std_dev),
+            percentile(0.0),
+            percentile(5.0),
+            percentile(50.0),
+            percentile(95.0),
+            percentile(100.0)
+        );
+        let histogram = self.histogram(10);
+        let tallest = histogram.iter().map(|(_, count)| *count).max().unwrap_or(1);
+        for (low, count) in histogram {
+            let bar = "#".repeat((count * 50).div_ceil(tallest));
+            report.push_str(&format!("{:>14} {:>7} {}\n", format_value(low), count, bar));
+        }
+        report
+    }
+}
+
+/// Part 2 totals for piles shaped like `cards` (same number of cards, same list lengths) whose
+/// numbers are drawn uniformly from `1..=pool`. Random piles tend to snowball way past `u64`, so
+/// copies are counted as `f64`.
+pub fn random_piles(
+    cards: &[Card],
+    pool: u64,
+    runs: usize,
+    seed: u64,
+    overflow: Overflow,
+) -> Result<Distribution, OverflowError> {
+    let mut rng = Rng::new(seed);
+    let mut totals: Vec<f64> = vec![];
+    for _ in 0..runs {
+        let match_counts: Vec<usize> = cards
+            .iter()
+            .map(|card| {
+                let winning: HashSet<u64> =
+                    rng.distinct(pool, card.winning.len()).into_iter().collect();
+                rng.distinct(pool, card.have.len())
+                    .iter()
+                    .filter(|number| winning.contains(number))
+                    .count()
+            })
+            .collect();
+        totals.push(cascade::cascade::<f64>(&match_counts, overflow)?.total());
+    }
+    Ok(Distribution::new(totals))
+}
+
+/// Change of the part 2 total when one of our numbers on one card is flipped, from winning to
+/// losing or the other way around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flip {
+    pub card: usize,
+    /// How many of the card's numbers give this change.
+    pub numbers: usize,
+    pub gained_match: bool,
+    pub delta: i64,
+}
+
+/// Every single-number flip, one entry per card and direction. Flipping any losing number of a
+/// card has the same effect, as has flipping any winning one.
+pub fn flip_sensitivity(cards: &[Card], overflow: Overflow) -> Result<Vec<Flip>, OverflowError> {
+    let match_counts: Vec<usize> = cards.iter().map(|card| card.match_count()).collect();
+    let base = cascade::cascade::<u64>(&match_counts, overflow)?.total() as i64;
+
+    let mut flips: Vec<Flip> = vec![];
+    for (index, card) in cards.iter().enumerate() {
+        let matches = match_counts[index];
+        for (gained_match, numbers) in [(true, card.have.len() - matches), (false, matches)] {
+            if numbers == 0 {
+                continue;
+            }
+            let mut flipped = match_counts.clone();
+            flipped[index] = match gained_match {
+                true => matches + 1,
+                false => matches - 1,
+            };
+            flips.push(Flip {
+                card: card.id,
+                numbers,
+                gained_match,
+                delta: cascade::cascade::<u64>(&flipped, overflow)?.total() as i64 - base,
+            });
+        }
+    }
+    Ok(flips)
+}
+
+/// Deltas of every flip, weighted by how many numbers give it.
+pub fn flip_distribution(flips: &[Flip]) -> Distribution {
+    Distribution::new(
+        flips
+            .iter()
+            .flat_map(|flip| std::iter::repeat_n(flip.delta as f64, flip.numbers))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cards() -> Vec<Card> {
+        [
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+            "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+            "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+            "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+            "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+            "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        ]
+        .iter()
+        .map(|line| Card::parse(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(7);
+        let numbers = rng.distinct(10, 10);
+        let unique: HashSet<u64> = numbers.iter().copied().collect();
+        assert_eq!(unique.len(), 10);
+        assert!(numbers.iter().all(|n| (1..=10).contains(n)));
+        assert_eq!(Rng::new(7).distinct(10, 10), numbers);
+
+        // A pool far too big to lay out
+        let numbers = rng.distinct(u64::MAX, 25);
+        let unique: HashSet<u64> = numbers.iter().copied().collect();
+        assert_eq!(unique.len(), 25);
+        assert!(numbers.iter().all(|n| *n >= 1));
+    }
+
+    #[test]
+    fn test_empty_distribution() {
+        let empty = Distribution::new(vec![]);
+        assert_eq!(empty.mean(), None);
+        assert_eq!(empty.std_dev(), None);
+        assert_eq!(empty.percentile(50.0), None);
+        assert_eq!(empty.histogram(10), vec![]);
+        assert_eq!(empty.report(), "samples 0\n");
+        let runs = random_piles(&cards(), 99, 0, 1, Overflow::Clamp).unwrap();
+        assert_eq!(runs.len(), 0);
+    }
+
+    #[test]
+    fn test_random_piles() {
+        let cards = cards();
+        let first = random_piles(&cards, 99, 200, 1, Overflow::Clamp).unwrap();
+        let again = random_piles(&cards, 99, 200, 1, Overflow::Clamp).unwrap();
+        assert_eq!(first, again);
+        assert_eq!(first.len(), 200);
+        assert!(first.percentile(0.0).unwrap() >= 6.0);
+
+        // With a pool as small as the lists every number matches
+        let full = random_piles(&cards, 8, 5, 1, Overflow::Clamp).unwrap();
+        assert!(full.percentile(0.0).unwrap() > first.percentile(100.0).unwrap());
+    }
+
+    #[test]
+    fn test_flips() {
+        let flips = flip_sensitivity(&cards(), Overflow::Clamp).unwrap();
+        // Card 6 has nothing after it, so flipping its numbers changes nothing
+        assert!(flips
+            .iter()
+            .filter(|flip| flip.card == 6)
+            .all(|flip| flip.delta == 0));
+        let first = flips
+            .iter()
+            .find(|flip| flip.card == 1 && !flip.gained_match)
+            .unwrap();
+        assert_eq!(first.numbers, 4);
+        assert!(first.delta < 0);
+        assert_eq!(flip_distribution(&flips).len(), 6 * 8);
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashMap, fmt};
+
+use crate::range::{Range, RangeError, RangeList, SeedRange};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlmanacError {
+    Parse {
+        line: usize,
+        message: String,
+    },
+    Range {
+        line: usize,
+        error: RangeError,
+    },
+    /// Two maps start from the same category, so the chain would be ambiguous.
+    DuplicateSource {
+        line: usize,
+        from: String,
+    },
+    UnknownCategory(String),
+    /// The chain going out of `from` never reaches `to`, stopping at `stuck` or going round in
+    /// circles.
+    NoPath {
+        from: String,
+        to: String,
+        stuck: String,
+    },
+    NoSeeds,
+    /// The seed line has an odd number of values, so the last seed has no range length.
+    UnpairedSeed(u64),
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlmanacError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            AlmanacError::Range { line, error } => write!(f, "line {}: {}", line, error),
+            AlmanacError::DuplicateSource { line, from } => {
+                write!(f, "line {}: there is already a map from '{}'", line, from)
+            }
+            AlmanacError::UnknownCategory(category) => {
+                write!(f, "no map goes from or to '{}'", category)
+            }
+            AlmanacError::NoPath { from, to, stuck } => {
+                write!(
+                    f,
+                    "can't get from '{}' to '{}', the maps stop at '{}'",
+                    from, to, stuck
+                )
+            }
+            AlmanacError::NoSeeds => write!(f, "there are no seeds to plant"),
+            AlmanacError::UnpairedSeed(seed) => {
+                write!(f, "seed {} has no range length after it", seed)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Map {
+    pub to: String,
+    pub ranges: RangeList,
+}
+
+/// The seeds plus every `X-to-Y map:` section, keyed by source category. Sections can come in any
+/// order and there can be any number of them, as long as each category has at most one way out.
+#[derive(Debug, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    maps: HashMap<String, Map>,
+}
+
+fn parse_numbers(line: &str, number: usize) -> Result<Vec<u64>, AlmanacError> {
+    line.split_whitespace()
+        .map(|item| {
+            item.parse::<u64>().map_err(|_| AlmanacError::Parse {
+                line: number,
+                message: format!("'{}' is not a number", item),
+            })
+        })
+        .collect()
+}
+
+impl Almanac {
+    pub fn parse(lines: &[String]) -> Result<Almanac, AlmanacError> {
+        let mut seeds: Option<Vec<u64>> = None;
+        let mut maps: HashMap<String, Map> = HashMap::new();
+        // Source category of the section being read
+        let mut current: Option<String> = None;
+
+        for (i, line) in lines.iter().enumerate() {
+            let number = i + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(list) = line.strip_prefix("seeds:") {
+                seeds = Some(parse_numbers(list, number)?);
+                current = None;
+            } else if let Some(header) = line.strip_suffix(" map:") {
+                let (from, to) = header.split_once("-to-").ok_or(AlmanacError::Parse {
+                    line: number,
+                    message: format!("expected 'X-to-Y map:', got '{}'", line),
+                })?;
+                if maps.contains_key(from) {
+                    return Err(AlmanacError::DuplicateSource {
+                        line: number,
+                        from: from.to_string(),
+                    });
+                }
+                maps.insert(
+                    from.to_string(),
+                    Map {
+                        to: to.to_string(),
+                        ranges: RangeList::new(),
+                    },
+                );
+                current = Some(from.to_string());
+            } else {
+                let Some(map) = current.as_ref().and_then(|from| maps.get_mut(from)) else {
+                    return Err(AlmanacError::Parse {
+                        line: number,
+                        message: format!("'{}' is outside of any map", line),
+                    });
+                };
+                let [destination_start, source_start, length] = parse_numbers(line, number)?[..]
+                else {
+                    return Err(AlmanacError::Parse {
+                        line: number,
+                        message: format!("expected 3 numbers, got '{}'", line),
+                    });
+                };
+                if length == 0 {
+                    continue;
+                }
+                let source_end =
+                    source_start
+                        .checked_add(length - 1)
+                        .ok_or(AlmanacError::Parse {
+                            line: number,
+                            message: format!("source range of '{}' goes past {}", line, u64::MAX),
+                        })?;
+                Range::new(source_start, source_end, destination_start)
+                    .and_then(|range| map.ranges.add_range(range))
+                    .map_err(|error| AlmanacError::Range {
+                        line: number,
+                        error,
+                    })?;
+            }
+        }
+
+        Ok(Almanac {
+            seeds: seeds.ok_or(AlmanacError::Parse {
+                line: 1,
+                message: "missing 'seeds:' line".to_string(),
+            })?,
+            maps,
+        })
+    }
+
+    /// Seed line read as `start length` pairs, cut short at `u64::MAX`. Empty ranges are skipped.
+    pub fn seed_ranges(&self) -> Result<Vec<SeedRange>, AlmanacError> {
+        if self.seeds.len() % 2 == 1 {
+            return Err(AlmanacError::UnpairedSeed(*self.seeds.last().unwrap()));
+        }
+        Ok(self
+            .seeds
+            .chunks(2)
+            .filter(|pair| pair[1] > 0)
+            .map(|pair| SeedRange {
+                start: pair[0],
+                end: pair[0].saturating_add(pair[1] - 1),
+            })
+            .collect())
+    }
+
+    fn knows(&self, category: &str) -> bool {
+        self.maps.contains_key(category) || self.maps.values().any(|map| map.to == category)
+    }
+
+    /// The maps to go through, in order, to get from `from` to `to`.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&Map>, AlmanacError> {
+        for category in [from, to] {
+            if !self.knows(category) {
+                return Err(AlmanacError::UnknownCategory(category.to_string()));
+            }
+        }
+
+        let mut path: Vec<&Map> = vec![];
+        let mut category = from;
+        while category != to {
+            // Every step leaves a different category, so a longer path has gone round a loop
+            let next = match self.maps.get(category) {
+                Some(map) if path.len() < self.maps.len() => map,
+                _ => {
+                    return Err(AlmanacError::NoPath {
+                        from: from.to_string(),
+                        to: to.to_string(),
+                        stuck: category.to_string(),
+                    })
+                }
+            };
+            path.push(next);
+            category = &next.to;
+        }
+        Ok(path)
+    }
+
+    pub fn convert(&self, from: &str, to: &str, value: u64) -> Result<u64, AlmanacError> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(value, |value, map| map.ranges.get_destination(value)))
+    }
+
+    /// The maps between `from` and `to` collapsed into a single one.
+    pub fn compose(&self, from: &str, to: &str) -> Result<RangeList, AlmanacError> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(RangeList::new(), |composed, map| {
+                RangeList::compose(&composed, &map.ranges)
+            }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|line| line.to_string()).collect()
+    }
+
+    const SHUFFLED: &str = "seeds: 79 14 55 13
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+soil-to-water map:
+10 20 5
+
+water-to-light map:
+88 18 7
+18 25 70
+
+humidity-to-location map:
+";
+
+    #[test]
+    fn test_shuffled_and_skipped_maps() {
+        let almanac = Almanac::parse(&lines(SHUFFLED)).unwrap();
+        assert_eq!(almanac.seeds, vec![79, 14, 55, 13]);
+        assert_eq!(almanac.path("seed", "temperature").unwrap().len(), 4);
+        assert_eq!(almanac.convert("seed", "soil", 79).unwrap(), 81);
+        assert_eq!(almanac.convert("soil", "water", 22).unwrap(), 12);
+        assert_eq!(almanac.convert("water", "water", 22).unwrap(), 22);
+        assert_eq!(almanac.convert("seed", "temperature", 79).unwrap(), 78);
+        let composed = almanac.compose("seed", "temperature").unwrap();
+        for seed in 0..120 {
+            assert_eq!(
+                composed.get_destination(seed),
+                almanac.convert("seed", "temperature", seed).unwrap()
+            );
+        }
+
+        assert_eq!(
+            almanac.path("seed", "colour").err(),
+            Some(AlmanacError::UnknownCategory("colour".to_string()))
+        );
+        assert_eq!(
+            almanac.path("water", "soil").err(),
+            Some(AlmanacError::NoPath {
+                from: "water".to_string(),
+                to: "soil".to_string(),
+                stuck: "temperature".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_bad_almanacs() {
+        let almanac =
+            Almanac::parse(&lines("seeds: 1\na-to-b map:\n1 2 3\nb-to-a map:\n")).unwrap();
+        assert!(matches!(
+            almanac.path("a", "c"),
+            Err(AlmanacError::UnknownCategory(_))
+        ));
+        assert!(matches!(
+            Almanac::parse(&lines("seeds: 1\na-to-b map:\na-to-c map:\n")),
+            Err(AlmanacError::DuplicateSource { line: 3, .. })
+        ));
+        assert!(matches!(
+            Almanac::parse(&lines("seeds: 1\n1 2 3\n")),
+            Err(AlmanacError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            Almanac::parse(&lines("seeds: 1\na-to-b map:\n1 2\n")),
+            Err(AlmanacError::Parse { line: 3, .. })
+        ));
+        assert!(Almanac::parse(&lines("a-to-b map:\n")).is_err());
+        assert!(matches!(
+            Almanac::parse(&lines("seeds: 1\na-to-b map:\n0 10 5\n\n20 14 2\n")),
+            Err(AlmanacError::Range {
+                line: 5,
+                error: RangeError::Overlap { .. }
+            })
+        ));
+
+        let unpaired = Almanac::parse(&lines("seeds: 79 14 55\n")).unwrap();
+        assert_eq!(unpaired.seeds, vec![79, 14, 55]);
+        assert_eq!(unpaired.seed_ranges(), Err(AlmanacError::UnpairedSeed(55)));
+
+        let looping = Almanac::parse(&lines("seeds: 1\na-to-b map:\nb-to-a map:\nc-to-d map:\n"));
+        assert!(matches!(
+            looping.unwrap().path("a", "d"),
+            Err(AlmanacError::NoPath { .. })
+        ));
+    }
+}
@@ -1,220 +1,226 @@
+mod almanac;
 mod range;
 
 use std::{
-    collections::HashSet,
+    env,
     fs::File,
     io::{BufRead, BufReader},
-    sync::Arc,
-    thread::{self, JoinHandle},
+    process,
 };
 
-use range::{Range, RangeList};
+use almanac::{Almanac, AlmanacError};
+use range::SeedRange;
 
-use crate::range::SeedRange;
+fn part1(almanac: &Almanac) -> Result<u64, AlmanacError> {
+    let path = almanac.path("seed", "location")?;
 
-fn populate_range_list(
-    lines: &Vec<String>,
-    line_number: usize,
-    range_list: &mut RangeList,
-) -> usize {
-    let mut line_number = line_number;
-    loop {
-        if line_number >= lines.len() {
-            break;
-        }
-
-        let line = lines.get(line_number).unwrap();
-        if line.is_empty() {
-            break;
-        }
-
-        let line_split: Vec<u64> = line.split(" ").map(|n| n.parse::<u64>().unwrap()).collect();
-        let destination_start = line_split.get(0).unwrap();
-        let source_start = line_split.get(1).unwrap();
-        let range = line_split.get(2).unwrap();
-        let source_end = source_start + range - 1;
-
-        range_list.add_range(Range::new(*source_start, source_end, *destination_start));
-
-        line_number += 1;
-    }
-
-    line_number
-}
-
-fn part1(lines: Vec<String>) {
-    let seeds: HashSet<u64> = lines.get(0).unwrap()["seeds: ".len()..]
-        .split(" ")
-        .map(|seed| seed.parse::<u64>().unwrap())
-        .collect();
-
-    let mut line_number = 3; // Start from seed-to-soil map
-
-    let mut seed_to_soil = RangeList::new();
-    let mut soil_to_fertilizer = RangeList::new();
-    let mut fertilizer_to_water = RangeList::new();
-    let mut water_to_light = RangeList::new();
-    let mut light_to_temperature = RangeList::new();
-    let mut temperature_to_humidity = RangeList::new();
-    let mut humidity_to_location = RangeList::new();
-
-    line_number = populate_range_list(&lines, line_number, &mut seed_to_soil) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut soil_to_fertilizer) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut fertilizer_to_water) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut water_to_light) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut light_to_temperature) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut temperature_to_humidity) + 2;
-    let _ = populate_range_list(&lines, line_number, &mut humidity_to_location) + 2;
-
-    let min_distance = seeds
+    let min_distance = almanac
+        .seeds
         .iter()
         .map(|seed| {
-            let soil = seed_to_soil.get_destination(*seed);
-            let fertilizer = soil_to_fertilizer.get_destination(soil);
-            let water = fertilizer_to_water.get_destination(fertilizer);
-            let light = water_to_light.get_destination(water);
-            let temperature = light_to_temperature.get_destination(light);
-            let humidity = temperature_to_humidity.get_destination(temperature);
-            return humidity_to_location.get_destination(humidity);
+            path.iter()
+                .fold(*seed, |value, map| map.ranges.get_destination(value))
         })
         .min()
-        .unwrap();
+        .ok_or(AlmanacError::NoSeeds)?;
 
-    println!("Part 1: {}", min_distance);
+    Ok(min_distance)
 }
 
-/// The solution that you write after a long day at work and then failing
-/// at basic math to do the problem efficiently
-fn part2(lines: Vec<String>) {
-    let seed_line_split: Vec<u64> = lines.get(0).unwrap()["seeds: ".len()..]
-        .split(" ")
-        .map(|n| n.parse::<u64>().unwrap())
-        .collect();
-
-    let mut i: usize = 0;
-    let mut seed_ranges: Vec<SeedRange> = Vec::new();
-    loop {
-        if i >= seed_line_split.len() {
-            break;
-        }
+/// Pushes the seed ranges as whole intervals through the maps collapsed into a single
+/// seed-to-location one, so the work depends on the number of ranges instead of seeds.
+fn part2(almanac: &Almanac) -> Result<u64, AlmanacError> {
+    lowest_location(almanac, &almanac.seed_ranges()?)
+}
 
-        let start = seed_line_split.get(i).unwrap();
-        let range = seed_line_split.get(i + 1).unwrap();
-        let end = start + range - 1;
+fn lowest_location(almanac: &Almanac, seed_ranges: &[SeedRange]) -> Result<u64, AlmanacError> {
+    let locations = almanac.compose("seed", "location")?.map_ranges(seed_ranges);
 
-        seed_ranges.push(SeedRange::new(*start, end));
+    // Normalized ranges are sorted, so the lowest location starts the first one
+    locations
+        .first()
+        .map(|range| range.start)
+        .ok_or(AlmanacError::NoSeeds)
+}
 
-        i += 2;
-    }
+fn usage() -> ! {
+    println!("Usage: problem_5");
+    println!("       problem_5 convert <from> <to> [value...]");
+    println!("                 converts the seeds when no values are given, e.g. convert soil humidity 81");
+    println!("       problem_5 seeds-for <location> [last]");
+    println!("                 seeds ending up at the location, or anywhere up to the last one");
+    println!("       problem_5 lowest [start length...]");
+    println!(
+        "                 lowest location for the seed ranges, the almanac's when none are given"
+    );
+    process::exit(1);
+}
 
-    let mut line_number = 3; // Start from seed-to-soil map
-
-    let mut seed_to_soil = RangeList::new();
-    let mut soil_to_fertilizer = RangeList::new();
-    let mut fertilizer_to_water = RangeList::new();
-    let mut water_to_light = RangeList::new();
-    let mut light_to_temperature = RangeList::new();
-    let mut temperature_to_humidity = RangeList::new();
-    let mut humidity_to_location = RangeList::new();
-
-    line_number = populate_range_list(&lines, line_number, &mut seed_to_soil) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut soil_to_fertilizer) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut fertilizer_to_water) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut water_to_light) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut light_to_temperature) + 2;
-    line_number = populate_range_list(&lines, line_number, &mut temperature_to_humidity) + 2;
-    let _ = populate_range_list(&lines, line_number, &mut humidity_to_location) + 2;
-
-    let seed_to_soil = Arc::new(seed_to_soil);
-    let soil_to_fertilizer = Arc::new(soil_to_fertilizer);
-    let fertilizer_to_water = Arc::new(fertilizer_to_water);
-    let water_to_light = Arc::new(water_to_light);
-    let light_to_temperature = Arc::new(light_to_temperature);
-    let temperature_to_humidity = Arc::new(temperature_to_humidity);
-    let humidity_to_location = Arc::new(humidity_to_location);
-
-    let cpu_count = num_cpus::get();
-    while seed_ranges.len() < cpu_count {
-        seed_ranges.sort();
-
-        let seed_range = seed_ranges.pop().unwrap();
-
-        let half_point = seed_range.start + seed_range.get_lenght() / 2;
-        seed_ranges.push(SeedRange::new(seed_range.start, half_point));
-        seed_ranges.push(SeedRange::new(half_point, seed_range.end));
-    }
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let file = File::open("input.txt").unwrap();
+    let reader = BufReader::new(file);
 
-    seed_ranges.iter().for_each(|range| println!("{:?}", range));
-    let mut thread_handles: Vec<JoinHandle<u64>> = Vec::new();
-
-    for i in 0..cpu_count {
-        let seed_range = seed_ranges.get(i).unwrap().clone();
-        let seed_to_soil = Arc::clone(&seed_to_soil);
-        let soil_to_fertilizer = Arc::clone(&soil_to_fertilizer);
-        let fertilizer_to_water = Arc::clone(&fertilizer_to_water);
-        let water_to_light = Arc::clone(&water_to_light);
-        let light_to_temperature = Arc::clone(&light_to_temperature);
-        let temperature_to_humidity = Arc::clone(&temperature_to_humidity);
-        let humidity_to_location = Arc::clone(&humidity_to_location);
-
-        thread_handles.push(thread::spawn(move || {
-            println!(
-                "Thread {:?} started. Will process {} seeds",
-                thread::current().id(),
-                seed_range.get_lenght()
-            );
-            let mut min: Option<u64> = None;
-            for seed in seed_range.start..(seed_range.end + 1) {
-                if seed % 2000 == 0 {
-                    println!(
-                        "Tread {:?} {} seeds remaning",
-                        thread::current().id(),
-                        seed_range.end - seed
-                    );
+    let lines: Vec<String> = reader.lines().map(|line| line.unwrap()).collect();
+    let almanac = Almanac::parse(&lines).unwrap_or_else(|e| {
+        println!("Invalid almanac: {}", e);
+        process::exit(1);
+    });
+
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("convert") => {
+            let (Some(from), Some(to)) = (args.get(2), args.get(3)) else {
+                usage();
+            };
+            let values: Vec<u64> = match args.len() > 4 {
+                true => args[4..]
+                    .iter()
+                    .map(|value| value.parse::<u64>().unwrap_or_else(|_| usage()))
+                    .collect(),
+                false => almanac.seeds.clone(),
+            };
+            for value in values {
+                match almanac.convert(from, to, value) {
+                    Ok(converted) => println!("{} {} -> {} {}", from, value, to, converted),
+                    Err(e) => {
+                        println!("{}", e);
+                        process::exit(1);
+                    }
                 }
-                let soil = seed_to_soil.get_destination(seed);
-                let fertilizer = soil_to_fertilizer.get_destination(soil);
-                let water = fertilizer_to_water.get_destination(fertilizer);
-                let light = water_to_light.get_destination(water);
-                let temperature = light_to_temperature.get_destination(light);
-                let humidity = temperature_to_humidity.get_destination(temperature);
-                let distance = humidity_to_location.get_destination(humidity);
-                if let Some(m) = min {
-                    if m > distance {
-                        min = Some(distance);
+            }
+        }
+        Some("seeds-for") => {
+            let location = args
+                .get(2)
+                .and_then(|arg| arg.parse::<u64>().ok())
+                .unwrap_or_else(|| usage());
+            let inverse = almanac
+                .compose("seed", "location")
+                .unwrap_or_else(|e| {
+                    println!("{}", e);
+                    process::exit(1);
+                })
+                .invert();
+            let seed_ranges = almanac.seed_ranges().unwrap_or_else(|e| {
+                println!("{}", e);
+                process::exit(1);
+            });
+            let planted = |seed: u64| {
+                seed_ranges
+                    .iter()
+                    .any(|seed_range| seed_range.start <= seed && seed <= seed_range.end)
+            };
+            match args.get(3) {
+                Some(last) => {
+                    let last = last.parse::<u64>().unwrap_or_else(|_| usage());
+                    let locations = SeedRange::new(location, last).unwrap_or_else(|e| {
+                        println!("Invalid locations: {}", e);
+                        process::exit(1);
+                    });
+                    for seeds in inverse.source_ranges(&locations) {
+                        println!("{}..={}", seeds.start, seeds.end);
+                    }
+                }
+                None => {
+                    let seeds = inverse.sources(location);
+                    if seeds.is_empty() {
+                        println!("No seed reaches location {}", location);
+                    }
+                    for seed in seeds {
+                        match planted(seed) {
+                            true => println!("{} (in the seed ranges)", seed),
+                            false => println!("{}", seed),
+                        }
                     }
-                } else {
-                    min = Some(distance);
                 }
             }
-            println!("Min val {:?} {:?}", min, thread::current().id());
-            min.unwrap()
-        }));
-    }
-
-    let mut min: Option<u64> = None;
-    for thread in thread_handles {
-        let distance = thread.join().unwrap();
-        if let Some(m) = min {
-            if m > distance {
-                min = Some(distance);
+        }
+        Some("lowest") => {
+            let numbers: Vec<u64> = args[2..]
+                .iter()
+                .map(|arg| arg.parse::<u64>().unwrap_or_else(|_| usage()))
+                .collect();
+            if !numbers.len().is_multiple_of(2) || numbers.chunks(2).any(|pair| pair[1] == 0) {
+                usage();
+            }
+            let seed_ranges: Vec<SeedRange> = match numbers.is_empty() {
+                true => almanac.seed_ranges().unwrap_or_else(|e| {
+                    println!("{}", e);
+                    process::exit(1);
+                }),
+                false => numbers
+                    .chunks(2)
+                    .map(|pair| SeedRange {
+                        start: pair[0],
+                        end: pair[0].saturating_add(pair[1] - 1),
+                    })
+                    .collect(),
+            };
+            match lowest_location(&almanac, &seed_ranges) {
+                Ok(location) => println!("{}", location),
+                Err(e) => println!("{}", e),
+            }
+        }
+        Some(_) => usage(),
+        None => {
+            for (part, result) in [(1, part1(&almanac)), (2, part2(&almanac))] {
+                match result {
+                    Ok(location) => println!("Part {}: {}", part, location),
+                    Err(e) => println!("Part {}: {}", part, e),
+                }
             }
-        } else {
-            min = Some(distance);
         }
     }
+}
 
-    println!("\n");
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    println!("Part 2: {:?}", min);
-}
+    fn read(path: &str) -> Almanac {
+        let reader = BufReader::new(File::open(path).unwrap());
+        let lines: Vec<String> = reader.lines().map(|line| line.unwrap()).collect();
+        Almanac::parse(&lines).unwrap()
+    }
 
-fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
+    /// Walks every single seed through the maps, the way part 2 used to.
+    fn brute_force(almanac: &Almanac) -> u64 {
+        almanac
+            .seed_ranges()
+            .unwrap()
+            .iter()
+            .flat_map(|seed_range| seed_range.start..=seed_range.end)
+            .map(|seed| almanac.convert("seed", "location", seed).unwrap())
+            .min()
+            .unwrap()
+    }
 
-    let lines: Vec<String> = reader.lines().map(|line| line.unwrap()).collect();
+    #[test]
+    fn test_part2_matches_brute_force() {
+        let mut almanac = read("test.txt");
+        assert_eq!(part1(&almanac), Ok(35));
+        assert_eq!(part2(&almanac), Ok(46));
+        assert_eq!(brute_force(&almanac), 46);
+        assert_eq!(
+            lowest_location(&almanac, &[SeedRange::new(79, 79).unwrap()]),
+            Ok(82)
+        );
+        let inverse = almanac.compose("seed", "location").unwrap().invert();
+        assert!(inverse.sources(82).contains(&79));
+        assert!(inverse.sources(46).contains(&82));
+
+        // Ranges that straddle several map boundaries
+        for seeds in [vec![0, 100], vec![45, 12, 97, 6], vec![13, 1, 60, 40]] {
+            almanac.seeds = seeds;
+            assert_eq!(part2(&almanac), Ok(brute_force(&almanac)));
+        }
 
-    part1(lines.clone());
-    part2(lines.clone());
+        almanac.seeds = vec![79, 14, 55];
+        assert_eq!(part2(&almanac), Err(AlmanacError::UnpairedSeed(55)));
+        almanac.seeds = vec![79, 0];
+        assert_eq!(part2(&almanac), Err(AlmanacError::NoSeeds));
+        almanac.seeds = vec![];
+        assert_eq!(part1(&almanac), Err(AlmanacError::NoSeeds));
+        assert_eq!(part2(&almanac), Err(AlmanacError::NoSeeds));
+    }
 }
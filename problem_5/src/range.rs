@@ -1,4 +1,30 @@
-use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeError {
+    /// Start after end.
+    Inverted { start: u64, end: u64 },
+    /// The destination would run past `u64::MAX`.
+    DestinationOverflow(Range),
+    /// Source ranges of a list can't share values, or lookups would depend on insertion order.
+    Overlap { existing: Range, added: Range },
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Inverted { start, end } => {
+                write!(f, "range starts at {} after its end {}", start, end)
+            }
+            RangeError::DestinationOverflow(range) => {
+                write!(f, "destination of {} goes past {}", range, u64::MAX)
+            }
+            RangeError::Overlap { existing, added } => {
+                write!(f, "{} overlaps {}", added, existing)
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct Range {
@@ -7,15 +33,36 @@ pub struct Range {
     pub destination_start: u64,
 }
 
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}..={} -> {}",
+            self.source_start, self.source_end, self.destination_start
+        )
+    }
+}
+
 impl Range {
-    pub fn new(source_start: u64, source_end: u64, destination_start: u64) -> Range {
+    pub fn new(
+        source_start: u64,
+        source_end: u64,
+        destination_start: u64,
+    ) -> Result<Range, RangeError> {
         if source_start > source_end {
-            panic!("WTF M8");
+            return Err(RangeError::Inverted {
+                start: source_start,
+                end: source_end,
+            });
         }
-        Range {
+        let range = Range {
             source_start,
             source_end,
             destination_start,
+        };
+        match destination_start.checked_add(source_end - source_start) {
+            Some(_) => Ok(range),
+            None => Err(RangeError::DestinationOverflow(range)),
         }
     }
 
@@ -25,6 +72,20 @@ impl Range {
         }
         None
     }
+
+    /// The part of `seed_range` this range covers, mapped to the destination.
+    fn image(&self, seed_range: &SeedRange) -> Option<SeedRange> {
+        if seed_range.end < self.source_start || seed_range.start > self.source_end {
+            return None;
+        }
+
+        let start = seed_range.start.max(self.source_start);
+        let end = seed_range.end.min(self.source_end);
+        Some(SeedRange {
+            start: self.destination_start + (start - self.source_start),
+            end: self.destination_start + (end - self.source_start),
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -34,19 +95,32 @@ pub struct SeedRange {
 }
 
 impl SeedRange {
-    pub fn new(start: u64, end: u64) -> SeedRange {
+    pub fn new(start: u64, end: u64) -> Result<SeedRange, RangeError> {
         if start > end {
-            panic!("WTF M8");
+            return Err(RangeError::Inverted { start, end });
         }
-        SeedRange { start, end }
+        Ok(SeedRange { start, end })
     }
+}
 
-    pub fn get_lenght(&self) -> u64 {
-        return self.end - self.start;
+/// Sorts the ranges and merges the ones that overlap or touch, so a set of ranges doesn't keep
+/// growing as it goes through the maps.
+pub fn normalize(mut seed_ranges: Vec<SeedRange>) -> Vec<SeedRange> {
+    seed_ranges.sort();
+    let mut merged: Vec<SeedRange> = vec![];
+    for seed_range in seed_ranges {
+        match merged.last_mut() {
+            Some(last) if seed_range.start <= last.end.saturating_add(1) => {
+                last.end = last.end.max(seed_range.end);
+            }
+            _ => merged.push(seed_range),
+        }
     }
+    merged
 }
 
-#[derive(Debug, Clone)]
+/// Ranges sorted by source start, none of them sharing a source value.
+#[derive(Debug, Clone, Default)]
 pub struct RangeList {
     ranges: Vec<Range>,
 }
@@ -56,17 +130,443 @@ impl RangeList {
         RangeList { ranges: vec![] }
     }
 
-    pub fn add_range(&mut self, range: Range) {
-        self.ranges.push(range);
+    /// Index of the first range that doesn't end before `value`.
+    fn first_ending_from(&self, value: u64) -> usize {
+        self.ranges
+            .partition_point(|range| range.source_end < value)
+    }
+
+    /// Inserts the range in order, leaving the list untouched if it overlaps one already there.
+    pub fn add_range(&mut self, range: Range) -> Result<(), RangeError> {
+        let index = self.first_ending_from(range.source_start);
+        if let Some(existing) = self.ranges.get(index) {
+            if existing.source_start <= range.source_end {
+                return Err(RangeError::Overlap {
+                    existing: existing.clone(),
+                    added: range,
+                });
+            }
+        }
+        self.ranges.insert(index, range);
+        Ok(())
     }
 
     pub fn get_destination(&self, value: u64) -> u64 {
-        for range in self.ranges.iter() {
-            if let Some(destination) = range.get_destination(value) {
-                return destination;
+        self.ranges
+            .get(self.first_ending_from(value))
+            .and_then(|range| range.get_destination(value))
+            .unwrap_or(value)
+    }
+
+    /// The stretches of source values no range covers, which map to themselves, in order.
+    pub fn gaps(&self) -> impl Iterator<Item = SeedRange> + '_ {
+        let mut next_start = Some(0);
+        let mut ranges = self.ranges.iter();
+        std::iter::from_fn(move || loop {
+            let start = next_start?;
+            match ranges.next() {
+                Some(range) => {
+                    next_start = range.source_end.checked_add(1);
+                    if start < range.source_start {
+                        return Some(SeedRange {
+                            start,
+                            end: range.source_start - 1,
+                        });
+                    }
+                }
+                None => {
+                    next_start = None;
+                    return Some(SeedRange {
+                        start,
+                        end: u64::MAX,
+                    });
+                }
+            }
+        })
+    }
+
+    /// Every destination of the seeds in `seed_range`, as ranges in the order of the seeds they
+    /// come from. Whatever no range covers maps to itself.
+    pub fn map_range(&self, seed_range: &SeedRange) -> Vec<SeedRange> {
+        let mut mapped: Vec<SeedRange> = vec![];
+        let mut next = Some(seed_range.start);
+        for range in self.ranges[self.first_ending_from(seed_range.start)..]
+            .iter()
+            .take_while(|range| range.source_start <= seed_range.end)
+        {
+            let Some(start) = next else {
+                break;
+            };
+            if start < range.source_start {
+                mapped.push(SeedRange {
+                    start,
+                    end: range.source_start - 1,
+                });
+            }
+            mapped.extend(range.image(seed_range));
+            next = range.source_end.checked_add(1);
+        }
+
+        match next {
+            Some(start) if start <= seed_range.end => mapped.push(SeedRange {
+                start,
+                end: seed_range.end,
+            }),
+            _ => {}
+        }
+        mapped
+    }
+
+    pub fn map_ranges(&self, seed_ranges: &[SeedRange]) -> Vec<SeedRange> {
+        normalize(
+            seed_ranges
+                .iter()
+                .flat_map(|seed_range| self.map_range(seed_range))
+                .collect(),
+        )
+    }
+
+    /// The whole `u64` domain cut into pieces that each shift by a single offset, sorted by
+    /// source. Gaps show up as identity ranges.
+    fn segments(&self) -> Vec<Range> {
+        let mut segments: Vec<Range> = self.ranges.clone();
+        segments.extend(self.gaps().map(|gap| Range {
+            source_start: gap.start,
+            source_end: gap.end,
+            destination_start: gap.start,
+        }));
+        segments.sort_by_key(|segment| segment.source_start);
+        segments
+    }
+
+    /// Builds a list from sorted, non-overlapping segments, joining the ones that continue each
+    /// other and leaving out identity ones since gaps map to themselves anyway.
+    fn from_segments(segments: Vec<Range>) -> RangeList {
+        let mut ranges: Vec<Range> = vec![];
+        for segment in segments {
+            match ranges.last_mut() {
+                Some(last)
+                    if last.source_end.checked_add(1) == Some(segment.source_start)
+                        && last
+                            .destination_start
+                            .checked_add(last.source_end - last.source_start + 1)
+                            == Some(segment.destination_start) =>
+                {
+                    last.source_end = segment.source_end;
+                }
+                _ => ranges.push(segment),
+            }
+        }
+        ranges.retain(|range| range.source_start != range.destination_start);
+        RangeList { ranges }
+    }
+
+    /// A single list doing what applying `a` and then `b` does.
+    pub fn compose(a: &RangeList, b: &RangeList) -> RangeList {
+        let after = b.segments();
+        let mut composed: Vec<Range> = vec![];
+        for segment in a.segments() {
+            let image_start = segment.destination_start;
+            let image_end = image_start + (segment.source_end - segment.source_start);
+            // `after` covers the whole domain in order, so start at the piece holding image_start
+            let first = after.partition_point(|next| next.source_end < image_start);
+            for next in after[first..]
+                .iter()
+                .take_while(|next| next.source_start <= image_end)
+            {
+                let start = image_start.max(next.source_start);
+                let end = image_end.min(next.source_end);
+                composed.push(Range {
+                    source_start: segment.source_start + (start - image_start),
+                    source_end: segment.source_start + (end - image_start),
+                    destination_start: next.destination_start + (start - next.source_start),
+                });
+            }
+        }
+        RangeList::from_segments(composed)
+    }
+
+    pub fn invert(&self) -> Inverse {
+        let mut ranges: Vec<Range> = self
+            .segments()
+            .iter()
+            .map(|segment| Range {
+                source_start: segment.destination_start,
+                source_end: segment.destination_start + (segment.source_end - segment.source_start),
+                destination_start: segment.source_start,
+            })
+            .collect();
+        ranges.sort_by_key(|range| range.source_start);
+        Inverse { ranges }
+    }
+}
+
+/// A `RangeList` read backwards. Several values can map to the same destination and some
+/// destinations can't be reached at all, so a lookup gives every candidate instead of one value.
+#[derive(Debug, Clone)]
+pub struct Inverse {
+    ranges: Vec<Range>,
+}
+
+impl Inverse {
+    /// Every value mapping to `destination`, in increasing order.
+    pub fn sources(&self, destination: u64) -> Vec<u64> {
+        let mut sources: Vec<u64> = self
+            .ranges
+            .iter()
+            .filter_map(|range| range.get_destination(destination))
+            .collect();
+        sources.sort();
+        sources
+    }
+
+    /// Every value mapping into `destinations`, as ranges.
+    pub fn source_ranges(&self, destinations: &SeedRange) -> Vec<SeedRange> {
+        normalize(
+            self.ranges
+                .iter()
+                .filter_map(|range| range.image(destinations))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn range_list(ranges: &[(u64, u64, u64)]) -> RangeList {
+        let mut range_list = RangeList::new();
+        for (destination_start, source_start, length) in ranges {
+            let range = Range::new(
+                *source_start,
+                source_start + (length - 1),
+                *destination_start,
+            );
+            range_list.add_range(range.unwrap()).unwrap();
+        }
+        range_list
+    }
+
+    fn seed_range(start: u64, end: u64) -> SeedRange {
+        SeedRange::new(start, end).unwrap()
+    }
+
+    /// The list as it used to be: ranges kept in insertion order, first match wins.
+    struct Linear {
+        ranges: Vec<Range>,
+    }
+
+    impl Linear {
+        fn get_destination(&self, value: u64) -> u64 {
+            for range in self.ranges.iter() {
+                if let Some(destination) = range.get_destination(value) {
+                    return destination;
+                }
             }
+
+            value
         }
+    }
+
+    /// SplitMix64, enough to shake out ordering and boundary bugs without a dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, bound: u64) -> u64 {
+            self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            (z ^ (z >> 31)) % bound
+        }
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        assert_eq!(
+            Range::new(5, 4, 0),
+            Err(RangeError::Inverted { start: 5, end: 4 })
+        );
+        assert!(matches!(
+            Range::new(0, 10, u64::MAX - 5),
+            Err(RangeError::DestinationOverflow(_))
+        ));
+        assert!(Range::new(0, u64::MAX, 0).is_ok());
+        assert_eq!(
+            SeedRange::new(2, 1),
+            Err(RangeError::Inverted { start: 2, end: 1 })
+        );
 
-        value
+        let mut range_list = range_list(&[(50, 98, 2), (52, 50, 48)]);
+        let added = Range::new(97, 97, 0).unwrap();
+        assert_eq!(
+            range_list.add_range(added.clone()),
+            Err(RangeError::Overlap {
+                existing: Range::new(50, 97, 52).unwrap(),
+                added,
+            })
+        );
+        assert!(range_list
+            .add_range(Range::new(100, 100, 0).unwrap())
+            .is_ok());
+        assert!(range_list.add_range(Range::new(0, 49, 1).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_map_range_splits_at_boundaries() {
+        let seed_to_soil = range_list(&[(50, 98, 2), (52, 50, 48)]);
+        assert_eq!(
+            seed_to_soil.map_range(&seed_range(40, 99)),
+            vec![seed_range(40, 49), seed_range(52, 99), seed_range(50, 51)]
+        );
+        // Pieces that end up touching are merged back together
+        assert_eq!(
+            seed_to_soil.map_ranges(&[seed_range(40, 99)]),
+            vec![seed_range(40, 99)]
+        );
+        assert_eq!(
+            seed_to_soil.map_range(&seed_range(0, 10)),
+            vec![seed_range(0, 10)]
+        );
+        assert_eq!(
+            seed_to_soil.map_range(&seed_range(97, 120)),
+            vec![seed_range(99, 99), seed_range(50, 51), seed_range(100, 120)]
+        );
+
+        let everything = range_list(&[(0, 1, u64::MAX)]);
+        assert_eq!(
+            everything.map_range(&seed_range(0, u64::MAX)),
+            vec![seed_range(0, 0), seed_range(0, u64::MAX - 1)]
+        );
+    }
+
+    #[test]
+    fn test_gaps() {
+        let gaps: Vec<SeedRange> = range_list(&[(50, 98, 2), (52, 50, 48)]).gaps().collect();
+        assert_eq!(gaps, vec![seed_range(0, 49), seed_range(100, u64::MAX)]);
+
+        let gaps: Vec<SeedRange> = range_list(&[(9, 0, 3), (0, 3, 3), (20, 10, 1)])
+            .gaps()
+            .collect();
+        assert_eq!(gaps, vec![seed_range(6, 9), seed_range(11, u64::MAX)]);
+
+        assert_eq!(
+            RangeList::new().gaps().collect::<Vec<_>>(),
+            vec![seed_range(0, u64::MAX)]
+        );
+        assert_eq!(
+            range_list(&[(1, 0, u64::MAX), (0, u64::MAX, 1)])
+                .gaps()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_compose_and_invert() {
+        let lists = [
+            range_list(&[(50, 98, 2), (52, 50, 48)]),
+            range_list(&[(100, 10, 2), (0, 12, 10), (200, 30, 1), (7, 40, 3)]),
+            range_list(&[(0, 15, 37), (37, 52, 2), (39, 0, 15)]),
+            RangeList::new(),
+        ];
+        for a in lists.iter() {
+            for b in lists.iter() {
+                let composed = RangeList::compose(a, b);
+                let inverse = composed.invert();
+                for value in 0..300 {
+                    let destination = b.get_destination(a.get_destination(value));
+                    assert_eq!(composed.get_destination(value), destination);
+                    assert!(inverse.sources(destination).contains(&value));
+                }
+                for destination in 0..300 {
+                    for source in inverse.sources(destination) {
+                        assert_eq!(composed.get_destination(source), destination);
+                    }
+                }
+            }
+        }
+
+        // 10 and 11 move away and nothing moves in, while 7 is reached from itself and from two
+        // different ranges
+        let inverse = lists[1].invert();
+        assert_eq!(inverse.sources(10), vec![]);
+        assert_eq!(inverse.sources(7), vec![7, 19, 40]);
+        assert_eq!(inverse.sources(100), vec![10, 100]);
+        assert_eq!(
+            inverse.source_ranges(&seed_range(0, 4)),
+            vec![seed_range(0, 4), seed_range(12, 16)]
+        );
+    }
+
+    #[test]
+    fn test_matches_linear_lookup() {
+        let mut rng = Rng(5);
+        for _ in 0..200 {
+            let mut sorted = RangeList::new();
+            let mut linear = Linear { ranges: vec![] };
+            for _ in 0..rng.below(12) {
+                let start = rng.below(1000);
+                let range = Range::new(start, start + rng.below(100), rng.below(1000)).unwrap();
+                match sorted.add_range(range.clone()) {
+                    Ok(()) => linear.ranges.push(range),
+                    Err(RangeError::Overlap { existing, added }) => {
+                        assert!(existing.source_start <= added.source_end);
+                        assert!(added.source_start <= existing.source_end);
+                        assert!(linear.ranges.contains(&existing));
+                    }
+                    Err(e) => panic!("unexpected {}", e),
+                }
+            }
+
+            let gaps: Vec<SeedRange> = sorted.gaps().collect();
+            for value in (0..1200).chain([u64::MAX - 1, u64::MAX]) {
+                assert_eq!(sorted.get_destination(value), linear.get_destination(value));
+                let in_gap = gaps
+                    .iter()
+                    .any(|gap| gap.start <= value && value <= gap.end);
+                let covered = linear
+                    .ranges
+                    .iter()
+                    .any(|range| range.get_destination(value).is_some());
+                assert_ne!(in_gap, covered, "{}", value);
+            }
+
+            let start = rng.below(1100);
+            let seeds = seed_range(start, start + rng.below(200));
+            let mut expected: Vec<u64> = (seeds.start..=seeds.end)
+                .map(|seed| linear.get_destination(seed))
+                .collect();
+            expected.sort();
+            expected.dedup();
+            let mapped: Vec<u64> = sorted
+                .map_ranges(&[seeds])
+                .iter()
+                .flat_map(|seed_range| seed_range.start..=seed_range.end)
+                .collect();
+            assert_eq!(mapped, expected);
+        }
+    }
+
+    #[test]
+    fn test_map_range_matches_get_destination() {
+        let range_list = range_list(&[(100, 10, 2), (0, 12, 10), (200, 30, 1), (7, 40, 3)]);
+        for start in 0..50 {
+            for end in start..50 {
+                let mut expected: Vec<u64> = (start..=end)
+                    .map(|seed| range_list.get_destination(seed))
+                    .collect();
+                expected.sort();
+                expected.dedup();
+
+                let mapped: Vec<u64> = range_list
+                    .map_ranges(&[seed_range(start, end)])
+                    .iter()
+                    .flat_map(|seed_range| seed_range.start..=seed_range.end)
+                    .collect();
+                assert_eq!(mapped, expected, "{}..={}", start, end);
+            }
+        }
     }
 }
@@ -1,75 +1,139 @@
+mod model;
+mod natural;
+mod race;
+
 use std::{
+    env,
     fs::File,
     io::{BufRead, BufReader},
-    iter::zip,
+    process,
 };
 
-fn part1(lines: Vec<String>) {
-    let times: Vec<u64> = lines
-        .get(0)
-        .unwrap()
-        .split(" ")
-        .filter(|item| !item.is_empty())
-        .map(|item| item.parse::<u64>().unwrap())
-        .collect();
-
-    let distances: Vec<u64> = lines
-        .get(1)
-        .unwrap()
-        .split(" ")
-        .filter(|item| !item.is_empty())
-        .map(|item| item.parse::<u64>().unwrap())
-        .collect();
-
-    println!("Times:     {:?}\nDistances: {:?}", times, distances);
+use model::{BoatModel, Capped};
+use natural::Natural;
+use race::Race;
 
-    let mut result: u64 = 1;
-    for (time, distance) in zip(times, distances) {
-        for i in 0..time {
-            if i * (time - i) > distance {
-                result *= time - 2 * i + 1;
-                break;
-            }
+/// Prints the winning holds of the race and returns how many there are.
+fn ways<N: Natural>(race: &Race<N>) -> N {
+    match race.winning_holds() {
+        Some(interval) => {
+            println!(
+                "Time {} distance {}: hold {}",
+                race.time, race.distance, interval
+            );
+            interval.count()
+        }
+        None => {
+            println!(
+                "Time {} distance {}: no way to win",
+                race.time, race.distance
+            );
+            N::from_u64(0)
         }
     }
+}
 
-    println!("Part 1: {}", result);
+/// Product of the ways of each part 1 race.
+fn product(ways: &[u128]) -> Result<u128, String> {
+    ways.iter()
+        .try_fold(1_u128, |product, ways| product.checked_mul(*ways))
+        .ok_or_else(|| "part 1 product overflows".to_string())
 }
 
-fn part2(lines: Vec<String>) {
-    let mut time = String::from("");
-    lines
-        .get(0)
-        .unwrap()
-        .split(" ")
-        .filter(|item| !item.is_empty())
-        .for_each(|item| time.push_str(item));
-
-    let mut distance = String::from("");
-    lines
-        .get(1)
-        .unwrap()
-        .split(" ")
-        .filter(|item| !item.is_empty())
-        .for_each(|item| distance.push_str(item));
-
-    let time: u64 = time.parse().unwrap();
-    let distance: u64 = distance.parse().unwrap();
-
-    println!("Time:     {:?}\nDistance: {:?}", time, distance);
-
-    let mut result: u64 = 1;
-    for i in 0..time {
-        if i * (time - i) > distance {
-            result *= time - 2 * i + 1;
-            break;
-        }
+fn part1(lines: Vec<String>) -> Result<u128, String> {
+    let races = race::parse_races(&lines)?;
+    product(&races.iter().map(ways).collect::<Vec<u128>>())
+}
+
+fn part2(lines: Vec<String>) -> Result<String, String> {
+    let race = race::parse_kerned(&lines)?;
+
+    // Squares of anything up to u64::MAX still fit in a u128
+    let small = |value: Option<u128>| value.filter(|value| *value <= u64::MAX as u128);
+    match (small(race.time.to_u128()), small(race.distance.to_u128())) {
+        (Some(time), Some(distance)) => Ok(ways(&Race { time, distance }).to_string()),
+        _ => Ok(ways(&race).to_string()),
     }
+}
+
+/// Same as `ways`, with the boat going the way `model` says.
+fn model_ways(model: &dyn BoatModel, time: u64, record: u128) -> u128 {
+    let intervals = model::winning_holds(model, time, record);
+    let ways: u128 = intervals
+        .iter()
+        .map(|interval| interval.count() as u128)
+        .sum();
+    let holds: Vec<String> = intervals
+        .iter()
+        .map(|interval| format!("{}..={}", interval.first, interval.last))
+        .collect();
+    match holds.is_empty() {
+        true => println!("Time {} distance {}: no way to win", time, record),
+        false => println!(
+            "Time {} distance {}: hold {} ({} ways)",
+            time,
+            record,
+            holds.join(", "),
+            ways
+        ),
+    }
+    ways
+}
+
+fn model_part1(lines: &[String], model: &dyn BoatModel) -> Result<u128, String> {
+    let races = race::parse_races(lines)?;
+    product(
+        &races
+            .iter()
+            .map(|race| model_ways(model, race.time as u64, race.distance))
+            .collect::<Vec<u128>>(),
+    )
+}
+
+fn model_part2(lines: &[String], model: &dyn BoatModel) -> Result<u128, String> {
+    let race = race::parse_kerned(lines)?;
+    let time = race
+        .time
+        .to_u128()
+        .filter(|time| *time <= u64::MAX as u128)
+        .ok_or_else(|| format!("time {} is too long for a boat model", race.time))?;
+    let record = race
+        .distance
+        .to_u128()
+        .ok_or_else(|| format!("distance {} is too long for a boat model", race.distance))?;
+    Ok(model_ways(model, time as u64, record))
+}
+
+fn usage() -> ! {
+    println!("Usage: problem_6 [--model <model>] [--cap <max speed>]");
+    println!("       models: linear, quadratic:K, diminishing:TOP:HALF,");
+    println!("               phases:DURATION*RATE,..., catapult");
+    process::exit(1);
+}
 
-    println!("Part 2: {}", result);
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == name)?;
+    Some(args.get(position + 1).unwrap_or_else(|| usage()))
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    let model: Option<Box<dyn BoatModel>> = match (option(&args, "--model"), option(&args, "--cap"))
+    {
+        (None, None) => None,
+        (spec, cap) => {
+            let mut model = model::parse(spec.unwrap_or("linear")).unwrap_or_else(|e| {
+                println!("Invalid model: {}", e);
+                usage();
+            });
+            if let Some(cap) = cap {
+                let max = cap.parse::<u128>().unwrap_or_else(|_| usage());
+                model = Box::new(Capped { inner: model, max });
+            }
+            Some(model)
+        }
+    };
+
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
 
@@ -77,7 +141,7 @@ fn main() {
         .lines()
         .map(|line| {
             line.unwrap()
-                .split(":")
+                .split(':')
                 .collect::<Vec<&str>>()
                 .get(1)
                 .unwrap()
@@ -86,6 +150,55 @@ fn main() {
         })
         .collect();
 
-    part1(lines.clone());
-    part2(lines.clone());
+    let results = match model {
+        Some(model) => [
+            model_part1(&lines, model.as_ref()),
+            model_part2(&lines, model.as_ref()),
+        ]
+        .map(|result| result.map(|ways| ways.to_string())),
+        None => [
+            part1(lines.clone()).map(|ways| ways.to_string()),
+            part2(lines),
+        ],
+    };
+    let mut failed = false;
+    for (part, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(ways) => println!("Part {}: {}", part + 1, ways),
+            Err(e) => {
+                println!("Part {}: invalid races: {}", part + 1, e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Three races of about 2^64 ways each.
+    fn huge() -> Vec<String> {
+        vec![
+            "18446744073709551615 18446744073709551615 18446744073709551615".to_string(),
+            "0 0 0".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_part1_overflow() {
+        assert_eq!(part1(huge()), Err("part 1 product overflows".to_string()));
+        assert!(part2(huge()).is_ok());
+        assert_eq!(
+            model_part1(&huge(), &model::Catapult),
+            Err("part 1 product overflows".to_string())
+        );
+        assert_eq!(
+            part1(vec!["7  15   30".to_string(), "9  40  200".to_string()]),
+            Ok(288)
+        );
+    }
 }
@@ -0,0 +1,319 @@
+use crate::race::Interval;
+
+/// How distance behaves as the hold time grows, which decides how winning holds are searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// Never goes down, so the winning holds are everything from the first winner on.
+    Monotone,
+    /// Strictly rises, then strictly falls, with equal neighbours only at the very top.
+    Unimodal,
+    /// Anything else, every hold gets tried.
+    Unknown,
+}
+
+pub trait BoatModel {
+    /// Speed reached after charging for `hold` milliseconds.
+    fn speed(&self, hold: u64) -> u128;
+
+    fn distance(&self, hold: u64, total: u64) -> u128 {
+        self.speed(hold).saturating_mul((total - hold) as u128)
+    }
+
+    fn shape(&self) -> Shape;
+}
+
+/// The puzzle's boat, one millimetre per millisecond per millisecond held.
+pub struct Linear;
+
+impl BoatModel for Linear {
+    fn speed(&self, hold: u64) -> u128 {
+        hold as u128
+    }
+
+    fn shape(&self) -> Shape {
+        Shape::Unimodal
+    }
+}
+
+/// Speed grows with the square of the hold, `k·h²`.
+pub struct Quadratic {
+    pub k: u128,
+}
+
+impl BoatModel for Quadratic {
+    fn speed(&self, hold: u64) -> u128 {
+        self.k.saturating_mul(hold as u128 * hold as u128)
+    }
+
+    fn shape(&self) -> Shape {
+        Shape::Unimodal
+    }
+}
+
+/// Diminishing returns, `top·h / (h + half)`: half the top speed after `half` milliseconds.
+/// Rounding down leaves flat steps on the way up, so nothing is promised about the shape.
+pub struct Diminishing {
+    pub top: u128,
+    pub half: u64,
+}
+
+impl BoatModel for Diminishing {
+    fn speed(&self, hold: u64) -> u128 {
+        match hold as u128 + self.half as u128 {
+            0 => 0,
+            denominator => self.top.saturating_mul(hold as u128) / denominator,
+        }
+    }
+
+    fn shape(&self) -> Shape {
+        Shape::Unknown
+    }
+}
+
+/// Charges at each `(duration, rate)` phase in turn and keeps its speed once they run out. A
+/// faster phase after a slower one can make distance go back up, hence the unknown shape.
+pub struct Phases {
+    pub phases: Vec<(u64, u128)>,
+}
+
+impl BoatModel for Phases {
+    fn speed(&self, hold: u64) -> u128 {
+        let mut left = hold;
+        let mut speed: u128 = 0;
+        for (duration, rate) in self.phases.iter() {
+            let charged = left.min(*duration);
+            speed = speed.saturating_add(rate.saturating_mul(charged as u128));
+            left -= charged;
+        }
+        speed
+    }
+
+    fn shape(&self) -> Shape {
+        Shape::Unknown
+    }
+}
+
+/// Another model with its speed capped at `max`, going the capped speed for the time left.
+pub struct Capped {
+    pub inner: Box<dyn BoatModel>,
+    pub max: u128,
+}
+
+impl BoatModel for Capped {
+    fn speed(&self, hold: u64) -> u128 {
+        self.inner.speed(hold).min(self.max)
+    }
+
+    /// Past the cap distance only shrinks with the time left, so a unimodal model stays
+    /// unimodal. Nothing is known about the others.
+    fn shape(&self) -> Shape {
+        match self.inner.shape() {
+            Shape::Unimodal => Shape::Unimodal,
+            _ => Shape::Unknown,
+        }
+    }
+}
+
+/// Released from a catapult, the race clock only starts when the boat lets go, so holding longer
+/// never hurts.
+pub struct Catapult;
+
+impl BoatModel for Catapult {
+    fn speed(&self, hold: u64) -> u128 {
+        hold as u128
+    }
+
+    fn distance(&self, hold: u64, total: u64) -> u128 {
+        self.speed(hold) * total as u128
+    }
+
+    fn shape(&self) -> Shape {
+        Shape::Monotone
+    }
+}
+
+/// Reads `linear`, `quadratic:K`, `diminishing:TOP:HALF`, `phases:DURATION*RATE,...` or
+/// `catapult`.
+pub fn parse(spec: &str) -> Result<Box<dyn BoatModel>, String> {
+    let number = |item: &str| -> Result<u128, String> {
+        item.parse::<u128>()
+            .map_err(|_| format!("'{}' is not a number", item))
+    };
+    let parts: Vec<&str> = spec.split(':').collect();
+    match parts[..] {
+        ["linear"] => Ok(Box::new(Linear)),
+        ["quadratic", k] => Ok(Box::new(Quadratic { k: number(k)? })),
+        ["diminishing", top, half] => Ok(Box::new(Diminishing {
+            top: number(top)?,
+            half: half
+                .parse::<u64>()
+                .map_err(|_| format!("'{}' is not a number", half))?,
+        })),
+        ["phases", phases] => {
+            let phases = phases
+                .split(',')
+                .map(|phase| {
+                    let (duration, rate) = phase
+                        .split_once('*')
+                        .ok_or_else(|| format!("expected DURATION*RATE, got '{}'", phase))?;
+                    let duration = duration
+                        .parse::<u64>()
+                        .map_err(|_| format!("'{}' is not a number", duration))?;
+                    Ok((duration, number(rate)?))
+                })
+                .collect::<Result<Vec<(u64, u128)>, String>>()?;
+            Ok(Box::new(Phases { phases }))
+        }
+        ["catapult"] => Ok(Box::new(Catapult)),
+        _ => Err(format!("unknown model '{}'", spec)),
+    }
+}
+
+/// Smallest value in `low..=high` where `test` holds, `test` going from false to true once.
+fn first_true(mut low: u64, mut high: u64, test: impl Fn(u64) -> bool) -> Option<u64> {
+    if !test(high) {
+        return None;
+    }
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match test(middle) {
+            true => high = middle,
+            false => low = middle + 1,
+        }
+    }
+    Some(low)
+}
+
+/// Every run of holds going further than `record`, trying each one.
+pub fn scan(model: &dyn BoatModel, total: u64, record: u128) -> Vec<Interval<u64>> {
+    let mut intervals: Vec<Interval<u64>> = vec![];
+    for hold in 0..=total {
+        if model.distance(hold, total) <= record {
+            continue;
+        }
+        match intervals.last_mut() {
+            Some(interval) if interval.last + 1 == hold => interval.last = hold,
+            _ => intervals.push(Interval {
+                first: hold,
+                last: hold,
+            }),
+        }
+    }
+    intervals
+}
+
+/// Winning holds, binary searched when the model's shape allows it.
+pub fn winning_holds(model: &dyn BoatModel, total: u64, record: u128) -> Vec<Interval<u64>> {
+    let wins = |hold: u64| model.distance(hold, total) > record;
+    match model.shape() {
+        Shape::Monotone => first_true(0, total, wins)
+            .map(|first| Interval { first, last: total })
+            .into_iter()
+            .collect(),
+        Shape::Unimodal => {
+            let peak = first_true(0, total, |hold| {
+                hold == total || model.distance(hold, total) >= model.distance(hold + 1, total)
+            })
+            .unwrap();
+            if !wins(peak) {
+                return vec![];
+            }
+            let first = first_true(0, peak, wins).unwrap();
+            let last = first_true(peak, total, |hold| !wins(hold))
+                .map(|losing| losing - 1)
+                .unwrap_or(total);
+            vec![Interval { first, last }]
+        }
+        Shape::Unknown => scan(model, total, record),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::race::Race;
+
+    fn models() -> Vec<Box<dyn BoatModel>> {
+        vec![
+            Box::new(Linear),
+            Box::new(Quadratic { k: 3 }),
+            Box::new(Diminishing { top: 40, half: 5 }),
+            Box::new(Phases {
+                phases: vec![(10, 1), (5, 100)],
+            }),
+            Box::new(Capped {
+                inner: Box::new(Linear),
+                max: 12,
+            }),
+            Box::new(Capped {
+                inner: Box::new(Quadratic { k: 2 }),
+                max: 0,
+            }),
+            Box::new(Catapult),
+        ]
+    }
+
+    #[test]
+    fn test_search_matches_scan() {
+        for model in models() {
+            for total in 0..40 {
+                let top = (0..=total)
+                    .map(|hold| model.distance(hold, total))
+                    .max()
+                    .unwrap();
+                for record in (0..top + 2).step_by((top as usize / 50).max(1)) {
+                    assert_eq!(
+                        winning_holds(model.as_ref(), total, record),
+                        scan(model.as_ref(), total, record),
+                        "{} {}",
+                        total,
+                        record
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_linear_matches_closed_form() {
+        for (time, distance) in [(7, 9), (15, 40), (30, 200), (53717880, 275118112151524)] {
+            let expected = Race {
+                time: time as u128,
+                distance,
+            }
+            .winning_holds()
+            .map(|interval| Interval {
+                first: interval.first as u64,
+                last: interval.last as u64,
+            });
+            assert_eq!(
+                winning_holds(&Linear, time, distance),
+                expected.into_iter().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_shapes() {
+        // Slows down before the 100x phase kicks in, so the winners come in two runs
+        let phases = Phases {
+            phases: vec![(10, 1), (5, 100)],
+        };
+        assert_eq!(
+            winning_holds(&phases, 15, 50),
+            vec![
+                Interval { first: 6, last: 9 },
+                Interval {
+                    first: 11,
+                    last: 14
+                }
+            ]
+        );
+
+        assert!(parse("quadratic:2").is_ok());
+        assert!(parse("phases:10*1,5*100").is_ok());
+        assert!(parse("phases:10").is_err());
+        assert!(parse("diminishing:40").is_err());
+        assert!(parse("warp").is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use std::{cmp::Ordering, fmt};
+
+/// Non-negative integers the race solver can work with, exactly.
+pub trait Natural: Clone + Ord + fmt::Display {
+    fn from_u64(value: u64) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    /// Panics when `other` is bigger, like `u128` subtraction does in debug builds.
+    fn sub(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+    fn half(&self) -> Self;
+    /// Largest `s` with `s * s <= self`.
+    fn isqrt(&self) -> Self;
+}
+
+impl Natural for u64 {
+    fn from_u64(value: u64) -> u64 {
+        value
+    }
+
+    fn add(&self, other: &u64) -> u64 {
+        self + other
+    }
+
+    fn sub(&self, other: &u64) -> u64 {
+        self - other
+    }
+
+    fn mul(&self, other: &u64) -> u64 {
+        self * other
+    }
+
+    fn half(&self) -> u64 {
+        self / 2
+    }
+
+    fn isqrt(&self) -> u64 {
+        u64::isqrt(*self)
+    }
+}
+
+impl Natural for u128 {
+    fn from_u64(value: u64) -> u128 {
+        value as u128
+    }
+
+    fn add(&self, other: &u128) -> u128 {
+        self + other
+    }
+
+    fn sub(&self, other: &u128) -> u128 {
+        self - other
+    }
+
+    fn mul(&self, other: &u128) -> u128 {
+        self * other
+    }
+
+    fn half(&self) -> u128 {
+        self / 2
+    }
+
+    fn isqrt(&self) -> u128 {
+        u128::isqrt(*self)
+    }
+}
+
+/// Arbitrary precision unsigned integer, little-endian base 2^32 limbs without trailing zeros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn normalized(mut limbs: Vec<u32>) -> BigUint {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    /// Parses a string of decimal digits, `None` if there is anything else in it.
+    pub fn parse(digits: &str) -> Option<BigUint> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut value = BigUint::from_u64(0);
+        for c in digits.chars() {
+            let digit = c.to_digit(10)?;
+            value = value.mul_small(10).add(&BigUint::from_u64(digit as u64));
+        }
+        Some(value)
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0, |value, limb| (value << 32) | *limb as u128),
+        )
+    }
+
+    fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 32 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    fn mul_small(&self, factor: u32) -> BigUint {
+        let mut carry: u64 = 0;
+        let mut limbs: Vec<u32> = Vec::with_capacity(self.limbs.len() + 1);
+        for limb in self.limbs.iter() {
+            let product = *limb as u64 * factor as u64 + carry;
+            limbs.push(product as u32);
+            carry = product >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::normalized(limbs)
+    }
+
+    /// Quotient and remainder.
+    fn div_small(&self, divisor: u32) -> (BigUint, u32) {
+        let mut remainder: u64 = 0;
+        let mut limbs: Vec<u32> = vec![0; self.limbs.len()];
+        for (i, limb) in self.limbs.iter().enumerate().rev() {
+            let current = (remainder << 32) | *limb as u64;
+            limbs[i] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        (BigUint::normalized(limbs), remainder as u32)
+    }
+
+    fn shl(&self, bits: usize) -> BigUint {
+        let (whole, part) = (bits / 32, bits % 32);
+        let mut limbs: Vec<u32> = vec![0; whole];
+        let mut carry: u32 = 0;
+        for limb in self.limbs.iter() {
+            limbs.push((limb << part) | carry);
+            carry = match part {
+                0 => 0,
+                _ => limb >> (32 - part),
+            };
+        }
+        limbs.push(carry);
+        BigUint::normalized(limbs)
+    }
+
+    fn shr(&self, bits: usize) -> BigUint {
+        let (whole, part) = (bits / 32, bits % 32);
+        if whole >= self.limbs.len() {
+            return BigUint { limbs: vec![] };
+        }
+        let limbs = &self.limbs[whole..];
+        let shifted = (0..limbs.len())
+            .map(|i| {
+                let high = match (part, limbs.get(i + 1)) {
+                    (0, _) | (_, None) => 0,
+                    (_, Some(next)) => next << (32 - part),
+                };
+                (limbs[i] >> part) | high
+            })
+            .collect();
+        BigUint::normalized(shifted)
+    }
+}
+
+impl Natural for BigUint {
+    fn from_u64(value: u64) -> BigUint {
+        BigUint::normalized(vec![value as u32, (value >> 32) as u32])
+    }
+
+    fn add(&self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs: Vec<u32> = Vec::with_capacity(len + 1);
+        let mut carry: u64 = 0;
+        for i in 0..len {
+            let sum = *self.limbs.get(i).unwrap_or(&0) as u64
+                + *other.limbs.get(i).unwrap_or(&0) as u64
+                + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::normalized(limbs)
+    }
+
+    fn sub(&self, other: &BigUint) -> BigUint {
+        if *self < *other {
+            panic!("attempt to subtract {} from {}", other, self);
+        }
+        let mut limbs: Vec<u32> = Vec::with_capacity(self.limbs.len());
+        let mut borrow: i64 = 0;
+        for (i, limb) in self.limbs.iter().enumerate() {
+            let mut difference = *limb as i64 - *other.limbs.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = 0;
+            if difference < 0 {
+                difference += 1 << 32;
+                borrow = 1;
+            }
+            limbs.push(difference as u32);
+        }
+        BigUint::normalized(limbs)
+    }
+
+    fn mul(&self, other: &BigUint) -> BigUint {
+        let mut limbs: Vec<u32> = vec![0; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry: u64 = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let current = limbs[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                limbs[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigUint::normalized(limbs)
+    }
+
+    fn half(&self) -> BigUint {
+        self.shr(1)
+    }
+
+    /// Digit by digit, two bits at a time, so it only needs shifts, comparisons and subtractions.
+    fn isqrt(&self) -> BigUint {
+        let mut remainder = self.clone();
+        let mut root = BigUint::from_u64(0);
+        // Highest power of four not above the value
+        let mut bit = match self.bits() {
+            0 => return root,
+            bits => BigUint::from_u64(1).shl((bits - 1) & !1),
+        };
+        while !bit.limbs.is_empty() {
+            let candidate = root.add(&bit);
+            if remainder >= candidate {
+                remainder = remainder.sub(&candidate);
+                root = root.shr(1).add(&bit);
+            } else {
+                root = root.shr(1);
+            }
+            bit = bit.shr(2);
+        }
+        root
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Nine decimal digits at a time, lowest group first
+        let mut groups: Vec<u32> = vec![];
+        let mut value = self.clone();
+        while !value.limbs.is_empty() {
+            let (quotient, group) = value.div_small(1_000_000_000);
+            groups.push(group);
+            value = quotient;
+        }
+        match groups.pop() {
+            None => write!(f, "0"),
+            Some(top) => {
+                write!(f, "{}", top)?;
+                groups
+                    .iter()
+                    .rev()
+                    .try_for_each(|group| write!(f, "{:09}", group))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn big(digits: &str) -> BigUint {
+        BigUint::parse(digits).unwrap()
+    }
+
+    #[test]
+    fn test_matches_u128() {
+        let values: [u128; 8] = [
+            0,
+            1,
+            7,
+            4_294_967_295,
+            4_294_967_296,
+            71_530,
+            940_200,
+            u64::MAX as u128,
+        ];
+        for a in values {
+            let big_a = big(&a.to_string());
+            assert_eq!(big_a.to_string(), a.to_string());
+            assert_eq!(big_a.isqrt().to_u128(), Some(a.isqrt()));
+            assert_eq!(big_a.half().to_u128(), Some(a / 2));
+            for b in values {
+                let big_b = big(&b.to_string());
+                assert_eq!(big_a.cmp(&big_b), a.cmp(&b));
+                assert_eq!(big_a.add(&big_b).to_u128(), Some(a + b));
+                assert_eq!(big_a.mul(&big_b).to_u128(), Some(a * b));
+                if a >= b {
+                    assert_eq!(big_a.sub(&big_b).to_u128(), Some(a - b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_past_u128() {
+        let n = big("123456789012345678901234567890123456789012345678901234567890");
+        assert_eq!(n.to_u128(), None);
+        assert_eq!(n.mul(&n).isqrt(), n);
+        assert_eq!(n.mul(&n).add(&n).add(&n).isqrt(), n);
+        assert_eq!(
+            n.mul(&n).add(&n).add(&n).add(&BigUint::from_u64(1)).isqrt(),
+            n.add(&BigUint::from_u64(1))
+        );
+        assert_eq!(
+            n.mul(&n).sub(&BigUint::from_u64(1)).isqrt(),
+            n.sub(&BigUint::from_u64(1))
+        );
+        assert_eq!(
+            n.mul(&BigUint::from_u64(1_000_000_007)).to_string(),
+            "123456789876543201987654320198765432019876543201987654320198641975230"
+        );
+        assert_eq!(BigUint::parse("12a"), None);
+        assert_eq!(BigUint::parse(""), None);
+    }
+}
@@ -0,0 +1,206 @@
+use std::{fmt, num::IntErrorKind};
+
+use crate::natural::{BigUint, Natural};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Race<N> {
+    pub time: N,
+    /// Record to beat.
+    pub distance: N,
+}
+
+/// Hold times that beat the record, both ends included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval<N> {
+    pub first: N,
+    pub last: N,
+}
+
+impl<N: Natural> Interval<N> {
+    pub fn count(&self) -> N {
+        self.last.sub(&self.first).add(&N::from_u64(1))
+    }
+}
+
+impl<N: Natural> fmt::Display for Interval<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..={} ({} ways)", self.first, self.last, self.count())
+    }
+}
+
+impl<N: Natural> Race<N> {
+    /// Holding `h` wins when `h * (T - h) > D`, i.e. strictly between the roots
+    /// `(T ± sqrt(T² - 4D)) / 2`. The first winning hold is one past the floor of the lower root,
+    /// worked out with an integer square root so nothing gets rounded.
+    pub fn winning_holds(&self) -> Option<Interval<N>> {
+        let one = N::from_u64(1);
+        let squared = self.time.mul(&self.time);
+        let four_distance = self.distance.mul(&N::from_u64(4));
+        if squared <= four_distance {
+            return None;
+        }
+
+        let discriminant = squared.sub(&four_distance);
+        let root = discriminant.isqrt();
+        let floor_lower_root = match root.mul(&root) == discriminant {
+            true => self.time.sub(&root).half(),
+            // The real root lies strictly between root and root + 1
+            false => self.time.sub(&root).sub(&one).half(),
+        };
+        let first = floor_lower_root.add(&one);
+        // Holding h goes as far as holding T - h
+        let last = self.time.sub(&first);
+        match first <= last {
+            true => Some(Interval { first, last }),
+            false => None,
+        }
+    }
+}
+
+fn values(line: &str) -> impl Iterator<Item = &str> {
+    line.split(' ').filter(|item| !item.is_empty())
+}
+
+/// One race per column, from the `Time:` and `Distance:` values.
+pub fn parse_races(lines: &[String]) -> Result<Vec<Race<u128>>, String> {
+    let parse = |line: Option<&String>| -> Result<Vec<u128>, String> {
+        values(line.ok_or("missing line")?)
+            .map(|item| match item.parse::<u64>() {
+                Ok(value) => Ok(value as u128),
+                Err(e) if *e.kind() == IntErrorKind::PosOverflow => {
+                    Err(format!("{} is too large for part 1", item))
+                }
+                Err(_) => Err(format!("'{}' is not a number", item)),
+            })
+            .collect()
+    };
+    let times = parse(lines.first())?;
+    let distances = parse(lines.get(1))?;
+    if times.len() != distances.len() {
+        return Err(format!(
+            "{} times but {} distances",
+            times.len(),
+            distances.len()
+        ));
+    }
+
+    Ok(times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, distance)| Race { time, distance })
+        .collect())
+}
+
+/// A single race read with the spaces between the digits ignored, however long it gets.
+pub fn parse_kerned(lines: &[String]) -> Result<Race<BigUint>, String> {
+    let parse = |line: Option<&String>| -> Result<BigUint, String> {
+        let digits: String = values(line.ok_or("missing line")?).collect();
+        BigUint::parse(&digits).ok_or_else(|| format!("'{}' is not a number", digits))
+    };
+    Ok(Race {
+        time: parse(lines.first())?,
+        distance: parse(lines.get(1))?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn wins(time: u128, distance: u128, hold: u128) -> bool {
+        hold <= time && hold * (time - hold) > distance
+    }
+
+    #[test]
+    fn test_matches_scan() {
+        for time in 0..60u128 {
+            for distance in 0..(time * time / 4 + 3) {
+                let expected: Vec<u128> = (0..=time)
+                    .filter(|hold| wins(time, distance, *hold))
+                    .collect();
+                let interval = Race { time, distance }.winning_holds();
+                match expected.first() {
+                    None => assert_eq!(interval, None, "{} {}", time, distance),
+                    Some(first) => assert_eq!(
+                        interval,
+                        Some(Interval {
+                            first: *first,
+                            last: *expected.last().unwrap()
+                        }),
+                        "{} {}",
+                        time,
+                        distance
+                    ),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_roots_and_large_races() {
+        // 4 * (12 - 4) = 32 exactly, which only ties
+        let interval = Race {
+            time: 12u128,
+            distance: 32,
+        }
+        .winning_holds()
+        .unwrap();
+        assert_eq!((interval.first, interval.last, interval.count()), (5, 7, 3));
+
+        // Big enough for i * (time - i) to overflow a u64
+        let time = u64::MAX as u128;
+        for distance in [0, time, time * 1000, (time / 2) * (time - time / 2) - 1] {
+            let interval = Race { time, distance }.winning_holds().unwrap();
+            assert!(wins(time, distance, interval.first));
+            assert!(!wins(time, distance, interval.first - 1));
+            assert!(wins(time, distance, interval.last));
+            assert!(!wins(time, distance, interval.last + 1));
+        }
+        let distance = (time / 2) * (time - time / 2);
+        assert_eq!(Race { time, distance }.winning_holds(), None);
+    }
+
+    #[test]
+    fn test_kerned_past_u128() {
+        let lines = vec![
+            "7 15 30 71530 940200 123456789 987654321 123456789012345".to_string(),
+            "9 40 200 94020 071530 987654321 123456789 000".to_string(),
+        ];
+        let race = parse_kerned(&lines).unwrap();
+        assert_eq!(race.time.to_u128(), None);
+        let interval = race.winning_holds().unwrap();
+
+        let check = |hold: &BigUint| hold.mul(&race.time.sub(hold)) > race.distance;
+        let one = BigUint::from_u64(1);
+        assert!(check(&interval.first) && check(&interval.last));
+        assert!(!check(&interval.first.sub(&one)));
+        assert!(!check(&interval.last.add(&one)));
+    }
+
+    #[test]
+    fn test_example() {
+        let lines = vec!["7  15   30".to_string(), "9  40  200".to_string()];
+        let ways: Vec<u128> = parse_races(&lines)
+            .unwrap()
+            .iter()
+            .map(|race| race.winning_holds().unwrap().count())
+            .collect();
+        assert_eq!(ways, vec![4, 8, 9]);
+        assert_eq!(
+            parse_kerned(&lines)
+                .unwrap()
+                .winning_holds()
+                .map(|i| i.count()),
+            Some(BigUint::from_u64(71503))
+        );
+        assert!(parse_races(&["1 2".to_string(), "3".to_string()]).is_err());
+        assert_eq!(
+            parse_races(&["18446744073709551616".to_string(), "0".to_string()]),
+            Err("18446744073709551616 is too large for part 1".to_string())
+        );
+        assert_eq!(
+            parse_races(&["7x".to_string(), "0".to_string()]),
+            Err("'7x' is not a number".to_string())
+        );
+    }
+}